@@ -5,50 +5,418 @@
 /// <https://docs.substrate.io/v3/runtime/frame>
 pub use pallet::*;
 
-// #[cfg(test)]
-// mod mock;
+pub mod migrations;
 
-// #[cfg(test)]
-// mod tests;
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
 
 // #[cfg(feature = "runtime-benchmarks")]
 // mod benchmarking;
 
 #[frame_support::pallet]
 pub mod pallet {
-	use frame_support::pallet_prelude::*;
+	use frame_support::{
+		pallet_prelude::*,
+		sp_runtime::traits::{AccountIdConversion, Saturating, Zero},
+		traits::{BalanceStatus, ConstU32, Currency, OnUnbalanced, ReservableCurrency},
+		transactional, CloneNoBound, PalletId, PartialEqNoBound, RuntimeDebugNoBound,
+	};
 	use frame_system::pallet_prelude::*;
 	use sp_std::vec::Vec;
-	
+
+	pub(crate) type DepositBalanceOf<T> = <<T as Config>::DepositCurrency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::Balance;
+	pub(crate) type NegativeImbalanceOf<T> = <<T as Config>::DepositCurrency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::NegativeImbalance;
+
+	pub type BrandNameOf<T> = BoundedVec<u8, <T as Config>::MaxNameLen>;
+	pub type CoalitionInfoOf<T> = Coalition<
+		<T as frame_system::Config>::AccountId,
+		<T as Config>::MaxNameLen,
+		<T as Config>::MaxCoalitionMembers,
+	>;
+	pub type OutletOf<T> =
+		Outlet<<T as frame_system::Config>::AccountId, <T as Config>::MaxNameLen>;
+
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Currency used to reserve brand deposits.
+		type DepositCurrency: ReservableCurrency<Self::AccountId>;
+
+		/// Deposit required per byte of brand profile data.
+		#[pallet::constant]
+		type ProfileDepositPerByte: Get<DepositBalanceOf<Self>>;
+
+		/// Maximum length of brand, coalition and outlet names.
+		#[pallet::constant]
+		type MaxNameLen: Get<u32>;
+
+		/// Maximum length of a single brand profile field.
+		#[pallet::constant]
+		type MaxProfileFieldLen: Get<u32>;
+
+		/// Pallets holding assets keyed by the brand account.
+		type BrandLifecycle: BrandLifecycle<Self::AccountId>;
+
+		/// How long holders have to redeem or refund their assets once a brand starts winding
+		/// down, before it can be removed.
+		#[pallet::constant]
+		type WindDownPeriod: Get<Self::BlockNumber>;
+
+		/// Origin allowed to verify or suspend brands.
+		type VerifierOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Bond reserved when a brand is created and released when it is removed cleanly.
+		#[pallet::constant]
+		type BrandDeposit: Get<DepositBalanceOf<Self>>;
+
+		/// Handler for brand bonds slashed by `VerifierOrigin`.
+		type Slashed: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// Used to derive the account holding the shared pool of each coalition.
+		#[pallet::constant]
+		type CoalitionPalletId: Get<PalletId>;
+
+		/// Maximum number of brands in a coalition.
+		#[pallet::constant]
+		type MaxCoalitionMembers: Get<u32>;
+	}
+
+	pub type CoalitionId = u32;
+
+	pub type OutletId = u32;
+
+	/// Longest geohash accepted for an outlet, about 3.7cm x 1.9cm precision.
+	pub type MaxGeohashLen = ConstU32<12>;
+
+	/// A physical store of a brand.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(NameLimit))]
+	pub struct Outlet<AccountId, NameLimit: Get<u32>> {
+		pub name: BoundedVec<u8, NameLimit>,
+		/// Base32 geohash of the store location.
+		pub geohash: BoundedVec<u8, MaxGeohashLen>,
+		/// Point of sale account allowed to issue points for this outlet.
+		pub pos_operator: Option<AccountId>,
+	}
+
+	/// A group of brands sharing one point currency, issued from the pool of the coalition
+	/// account (see [`Pallet::coalition_account`]).
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(NameLimit, MemberLimit))]
+	pub struct Coalition<AccountId, NameLimit: Get<u32>, MemberLimit: Get<u32>> {
+		pub name: BoundedVec<u8, NameLimit>,
+		pub members: BoundedVec<AccountId, MemberLimit>,
+	}
+
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum MembershipChange {
+		Join,
+		Leave,
+	}
+
+	/// A pending join or leave, applied once every other member has approved it.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(MemberLimit))]
+	pub struct MembershipProposal<AccountId, MemberLimit: Get<u32>> {
+		pub change: MembershipChange,
+		pub approvals: BoundedVec<AccountId, MemberLimit>,
 	}
 
-    #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
-	#[scale_info(skip_type_params(T))]
-	pub struct Brand {
-		pub name: Vec<u8>,
+	/// Verification badge of a brand, set by `VerifierOrigin`.
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum BrandStatus {
+		/// Registered but not reviewed yet.
+		Pending,
+		/// Confirmed to be the merchant it claims to be.
+		Verified,
+		/// Blocked from minting, issuing points and selling until reinstated.
+		Suspended,
+	}
+
+	impl Default for BrandStatus {
+		fn default() -> Self {
+			BrandStatus::Pending
+		}
+	}
+
+	/// How `remove_brand` treats assets the brand still has outstanding.
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub enum RemovalMode {
+		/// Refuse to remove the brand while any `BrandLifecycle` pallet still holds assets
+		/// for it.
+		Strict,
+		/// Announce the removal and give holders `WindDownPeriod` blocks to redeem or refund,
+		/// after which `finalize_brand_removal` releases the stake and burns what is left.
+		WindDown,
+	}
+
+	/// Hooks into the life of a brand, implemented by the pallets that keep state keyed by the
+	/// brand account.
+	///
+	/// Hooks that walk per-brand storage count every item they touch against `budget` (see
+	/// [`Pallet::use_item`]) and fail once it runs out, so callers can charge for the work.
+	pub trait BrandLifecycle<AccountId> {
+		/// Re-key everything held by the brand `from` to `to`, moving reserves along with it.
+		fn on_brand_transferred(
+			from: &AccountId,
+			to: &AccountId,
+			budget: &mut u32,
+		) -> DispatchResult;
+
+		/// Whether the brand still has stake, issued points, NFTs or other assets outstanding.
+		fn has_live_assets(brand: &AccountId) -> bool;
+
+		/// Release whatever the brand still has reserved and burn the remaining assets.
+		fn on_brand_removed(brand: &AccountId, budget: &mut u32) -> DispatchResult;
+	}
+
+	impl<AccountId> BrandLifecycle<AccountId> for () {
+		fn on_brand_transferred(
+			_from: &AccountId,
+			_to: &AccountId,
+			_budget: &mut u32,
+		) -> DispatchResult {
+			Ok(())
+		}
+
+		fn has_live_assets(_brand: &AccountId) -> bool {
+			false
+		}
+
+		fn on_brand_removed(_brand: &AccountId, _budget: &mut u32) -> DispatchResult {
+			Ok(())
+		}
+	}
+
+	impl<AccountId, A, B> BrandLifecycle<AccountId> for (A, B)
+	where
+		A: BrandLifecycle<AccountId>,
+		B: BrandLifecycle<AccountId>,
+	{
+		fn on_brand_transferred(
+			from: &AccountId,
+			to: &AccountId,
+			budget: &mut u32,
+		) -> DispatchResult {
+			A::on_brand_transferred(from, to, budget)?;
+			B::on_brand_transferred(from, to, budget)
+		}
+
+		fn has_live_assets(brand: &AccountId) -> bool {
+			A::has_live_assets(brand) || B::has_live_assets(brand)
+		}
+
+		fn on_brand_removed(brand: &AccountId, budget: &mut u32) -> DispatchResult {
+			A::on_brand_removed(brand, budget)?;
+			B::on_brand_removed(brand, budget)
+		}
+	}
+
+    #[derive(CloneNoBound, Encode, Decode, PartialEqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(NameLimit))]
+	#[codec(mel_bound())]
+	pub struct Brand<NameLimit: Get<u32>> {
+		pub name: BoundedVec<u8, NameLimit>,
+	}
+
+	/// Public facing metadata of a brand, shown by wallets and apps.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(StringLimit))]
+	pub struct BrandProfile<Balance, StringLimit: Get<u32>> {
+		pub logo_uri: BoundedVec<u8, StringLimit>,
+		pub website: BoundedVec<u8, StringLimit>,
+		pub description: BoundedVec<u8, StringLimit>,
+		pub contact: BoundedVec<u8, StringLimit>,
+		pub category: BoundedVec<u8, StringLimit>,
+		/// ISO 3166-1 alpha-2 country code.
+		pub country: Option<[u8; 2]>,
+		pub deposit: Balance,
+	}
+
+	/// A field of [`BrandProfile`], used to report what an update changed.
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub enum ProfileField {
+		LogoUri,
+		Website,
+		Description,
+		Contact,
+		Category,
+		Country,
+	}
+
+	/// Role of an operator acting on behalf of a brand. The brand account itself is always
+	/// treated as `Owner`.
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum BrandRole {
+		/// Full control, including managing other operators.
+		Owner,
+		/// Day-to-day management; can do everything a `Cashier` or `Minter` can.
+		Manager,
+		/// Issues points to customers at the point of sale.
+		Cashier,
+		/// Mints brand tokens and NFTs.
+		Minter,
+	}
+
+	impl BrandRole {
+		/// Whether an operator holding `self` may act where `required` is needed.
+		pub fn allows(&self, required: BrandRole) -> bool {
+			match self {
+				BrandRole::Owner => true,
+				BrandRole::Manager => required != BrandRole::Owner,
+				role => *role == required,
+			}
+		}
 	}
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
-	#[pallet::without_storage_info]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	// The pallet's runtime storage items.
 	// https://docs.substrate.io/v3/runtime/storage
 	#[pallet::storage]
 	#[pallet::getter(fn brand_by_id)]
-	pub type BrandById<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, Brand>;
+	pub type BrandById<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, Brand<T::MaxNameLen>>;
+
+	/// Reverse index of `BrandById`, keyed by the normalized brand name
+	/// (see [`Pallet::normalize_name`]).
+	#[pallet::storage]
+	#[pallet::getter(fn brand_id_by_name)]
+	pub type BrandIdByName<T: Config> =
+		StorageMap<_, Blake2_128Concat, BrandNameOf<T>, T::AccountId>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn brand_profile)]
+	pub type BrandProfileById<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		BrandProfile<DepositBalanceOf<T>, T::MaxProfileFieldLen>,
+	>;
+
+	/// Operators allowed to act on behalf of a brand, with their role.
+	#[pallet::storage]
+	#[pallet::getter(fn brand_operator)]
+	pub type BrandOperators<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Twox64Concat,
+		T::AccountId,
+		BrandRole,
+	>;
+
+	/// Ownership transfers proposed by a brand, waiting for the new account to accept.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_brand_transfer)]
+	pub type PendingBrandTransfer<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, T::AccountId>;
+
+	/// Brands winding down, with the block from which they can be removed.
+	#[pallet::storage]
+	#[pallet::getter(fn winding_down)]
+	pub type WindingDown<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, T::BlockNumber>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn brand_status)]
+	pub type BrandStatusOf<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, BrandStatus, ValueQuery>;
+
+	/// Registration bond currently reserved for each brand.
+	#[pallet::storage]
+	#[pallet::getter(fn brand_bond)]
+	pub type BrandBond<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, DepositBalanceOf<T>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn coalition)]
+	pub type Coalitions<T: Config> = StorageMap<_, Twox64Concat, CoalitionId, CoalitionInfoOf<T>>;
+
+	#[pallet::storage]
+	pub type NextCoalitionId<T: Config> = StorageValue<_, CoalitionId, ValueQuery>;
+
+	/// The coalition each brand belongs to; a brand is in at most one coalition.
+	#[pallet::storage]
+	#[pallet::getter(fn coalition_of)]
+	pub type CoalitionOf<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, CoalitionId>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn coalition_proposal)]
+	pub type CoalitionProposals<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		CoalitionId,
+		Twox64Concat,
+		T::AccountId,
+		MembershipProposal<T::AccountId, T::MaxCoalitionMembers>,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn outlet)]
+	pub type Outlets<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Twox64Concat,
+		OutletId,
+		OutletOf<T>,
+	>;
+
+	#[pallet::storage]
+	pub type NextOutletId<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, OutletId, ValueQuery>;
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		NewBrandCreated(T::AccountId, Brand),
-        BrandRemoved(T::AccountId, Brand)
+		NewBrandCreated(T::AccountId, Brand<T::MaxNameLen>),
+        BrandRemoved(T::AccountId, Brand<T::MaxNameLen>),
+		BrandProfileUpdated {
+			brand_id: T::AccountId,
+			changed: Vec<ProfileField>,
+			deposit: DepositBalanceOf<T>,
+		},
+		OperatorAdded { brand_id: T::AccountId, operator: T::AccountId, role: BrandRole },
+		OperatorRemoved { brand_id: T::AccountId, operator: T::AccountId },
+		BrandTransferProposed { brand_id: T::AccountId, new_owner: T::AccountId },
+		BrandTransferCancelled { brand_id: T::AccountId },
+		BrandTransferred { from: T::AccountId, to: T::AccountId },
+		/// The brand is being removed; holders can redeem or refund until `ends_at`.
+		BrandWindDownStarted { brand_id: T::AccountId, ends_at: T::BlockNumber },
+		BrandStatusChanged { brand_id: T::AccountId, status: BrandStatus },
+		BrandDepositSlashed { brand_id: T::AccountId, amount: DepositBalanceOf<T> },
+		CoalitionCreated { coalition_id: CoalitionId, founder: T::AccountId, account: T::AccountId },
+		CoalitionChangeProposed {
+			coalition_id: CoalitionId,
+			brand_id: T::AccountId,
+			change: MembershipChange,
+		},
+		CoalitionChangeApproved {
+			coalition_id: CoalitionId,
+			brand_id: T::AccountId,
+			approver: T::AccountId,
+		},
+		CoalitionJoined { coalition_id: CoalitionId, brand_id: T::AccountId },
+		CoalitionLeft { coalition_id: CoalitionId, brand_id: T::AccountId },
+		OutletAdded { brand_id: T::AccountId, outlet_id: OutletId },
+		OutletUpdated { brand_id: T::AccountId, outlet_id: OutletId },
+		OutletRemoved { brand_id: T::AccountId, outlet_id: OutletId },
 	}
 
 	#[pallet::error]
@@ -60,43 +428,842 @@ pub mod pallet {
         BrandNameExisted,
         ThisUserNotCreatedBrandBefore,
         BrandNotFound,
+		/// The brand name is empty once whitespace has been stripped.
+		InvalidBrandName,
+		/// A brand profile field is longer than `MaxProfileFieldLen`.
+		ProfileFieldTooLong,
+		/// The caller is not an operator of the brand with a sufficient role.
+		NotBrandOperator,
+		/// The brand account is already its own owner and cannot be added as an operator.
+		OperatorIsBrand,
+		OperatorNotFound,
+		/// The new owner already controls a brand.
+		NewOwnerHasBrand,
+		/// There is no pending transfer of this brand to the caller.
+		NoPendingTransfer,
+		/// Reserved deposits could not be moved to the new owner.
+		DepositNotMoved,
+		/// The brand still has assets outstanding; wind it down instead.
+		BrandHasLiveAssets,
+		/// The brand is winding down and cannot take on new obligations.
+		BrandWindingDown,
+		/// The brand is not winding down.
+		BrandNotWindingDown,
+		/// The wind-down period has not ended yet.
+		WindDownNotOver,
+		/// The brand has been suspended.
+		BrandSuspended,
+		/// Bonds can only be slashed once the brand has been suspended.
+		BrandNotSuspended,
+		CoalitionNotFound,
+		/// The brand already belongs to a coalition.
+		AlreadyInCoalition,
+		/// The brand is not a member of the coalition.
+		NotCoalitionMember,
+		/// A join or leave of this brand is already pending.
+		ProposalExists,
+		ProposalNotFound,
+		AlreadyApproved,
+		/// The last member cannot leave, the coalition pool would have no one to settle with.
+		LastCoalitionMember,
+		OutletNotFound,
+		/// The geohash is empty, too long or not in the geohash base32 alphabet.
+		InvalidGeohash,
+		/// A brand, coalition or outlet name is longer than `MaxNameLen`.
+		NameTooLong,
+		/// The coalition already has `MaxCoalitionMembers` members.
+		TooManyCoalitionMembers,
+		/// The brand holds more items than the caller allowed for.
+		TooManyBrandItems,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			crate::migrations::v1::migrate::<T>()
+				.saturating_add(crate::migrations::v2::migrate::<T>())
+		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
 		pub fn create_brand(origin: OriginFor<T>, name: Vec<u8>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			
-			let mut check = 0;
-			for brand in BrandById::<T>::iter_values() {
-				if brand.name == name {
-					check += 1;
-					break;
-				}
+			let name = Self::bounded_name(name)?;
+
+			let key = Self::name_key(&name);
+			ensure!(!key.is_empty(), Error::<T>::InvalidBrandName);
+			ensure!(!BrandIdByName::<T>::contains_key(&key), Error::<T>::BrandNameExisted);
+
+			if let Some(old_brand) = BrandById::<T>::get(&who) {
+				BrandIdByName::<T>::remove(Self::name_key(&old_brand.name));
+			} else {
+				let deposit = T::BrandDeposit::get();
+				T::DepositCurrency::reserve(&who, deposit)?;
+				BrandBond::<T>::insert(&who, deposit);
 			}
-            
-			ensure!(check == 0, Error::<T>::BrandNameExisted);
+
             let new_brand = Brand { 
                 name
             };
 
             BrandById::<T>::insert(&who, &new_brand);
+			BrandIdByName::<T>::insert(&key, &who);
 
 			Self::deposit_event(Event::NewBrandCreated(who, new_brand));
 			Ok(())
 		}
 
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn remove_brand(origin: OriginFor<T>) -> DispatchResult {
+		/// Remove the caller's brand. In `Strict` mode the brand is removed right away and only
+		/// if nothing is outstanding; in `WindDown` mode holders are given `WindDownPeriod`
+		/// blocks before `finalize_brand_removal` can clean up.
+		///
+		/// `max_items` bounds the number of per-brand items a `Strict` removal clears, as for
+		/// `accept_brand_transfer`. Suspended brands cannot be removed, so their bond stays
+		/// reserved until `VerifierOrigin` slashes it or reinstates the brand.
+		#[pallet::weight(
+			10_000 +
+				T::DbWeight::get().reads_writes(24, 32) +
+				Pallet::<T>::brand_items_weight(*max_items)
+		)]
+		#[transactional]
+		pub fn remove_brand(
+			origin: OriginFor<T>,
+			mode: RemovalMode,
+			max_items: u32,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+            ensure!(BrandById::<T>::contains_key(&who), Error::<T>::ThisUserNotCreatedBrandBefore);
+			ensure!(!WindingDown::<T>::contains_key(&who), Error::<T>::BrandWindingDown);
+			Self::ensure_not_suspended(&who)?;
+
+			let mut budget = max_items;
+			match mode {
+				RemovalMode::Strict => {
+					ensure!(
+						!T::BrandLifecycle::has_live_assets(&who),
+						Error::<T>::BrandHasLiveAssets
+					);
+					Self::do_remove_brand(&who, &mut budget)?;
+				},
+				RemovalMode::WindDown => {
+					let ends_at = frame_system::Pallet::<T>::block_number()
+						.saturating_add(T::WindDownPeriod::get());
+					WindingDown::<T>::insert(&who, ends_at);
+					PendingBrandTransfer::<T>::remove(&who);
+
+					Self::deposit_event(Event::BrandWindDownStarted { brand_id: who, ends_at });
+				},
+			}
+			let weight = 10_000 +
+				T::DbWeight::get().reads_writes(24, 32) +
+				Self::brand_items_weight(max_items - budget);
+			Ok(Some(weight).into())
+		}
+
+		/// Remove a brand whose wind-down period is over and that is not suspended. Anyone may
+		/// call this, allowing for `max_items` per-brand items to clear.
+		#[pallet::weight(
+			10_000 +
+				T::DbWeight::get().reads_writes(24, 32) +
+				Pallet::<T>::brand_items_weight(*max_items)
+		)]
+		#[transactional]
+		pub fn finalize_brand_removal(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			max_items: u32,
+		) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+
+			let ends_at = WindingDown::<T>::get(&brand_id).ok_or(Error::<T>::BrandNotWindingDown)?;
+			ensure!(
+				frame_system::Pallet::<T>::block_number() >= ends_at,
+				Error::<T>::WindDownNotOver
+			);
+			Self::ensure_not_suspended(&brand_id)?;
+
+			let mut budget = max_items;
+			Self::do_remove_brand(&brand_id, &mut budget)?;
+			let weight = 10_000 +
+				T::DbWeight::get().reads_writes(24, 32) +
+				Self::brand_items_weight(max_items - budget);
+			Ok(Some(weight).into())
+		}
+
+		/// Replace the profile of the caller's brand. Empty fields are cleared. The profile
+		/// deposit is recomputed from the new size and the difference reserved or released.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 1))]
+		pub fn update_brand_profile(
+			origin: OriginFor<T>,
+			logo_uri: Vec<u8>,
+			website: Vec<u8>,
+			description: Vec<u8>,
+			contact: Vec<u8>,
+			category: Vec<u8>,
+			country: Option<[u8; 2]>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(BrandById::<T>::contains_key(&who), Error::<T>::ThisUserNotCreatedBrandBefore);
+
+			let mut profile = BrandProfile {
+				logo_uri: Self::bounded_field(logo_uri)?,
+				website: Self::bounded_field(website)?,
+				description: Self::bounded_field(description)?,
+				contact: Self::bounded_field(contact)?,
+				category: Self::bounded_field(category)?,
+				country,
+				deposit: Zero::zero(),
+			};
+
+			let data_compressed = profile.logo_uri.len() +
+				profile.website.len() +
+				profile.description.len() +
+				profile.contact.len() +
+				profile.category.len() +
+				profile.country.map_or(0, |code| code.len());
+			let data_deposit =
+				T::ProfileDepositPerByte::get().saturating_mul((data_compressed as u32).into());
+
+			let old_profile = BrandProfileById::<T>::get(&who);
+			let old_deposit = old_profile.as_ref().map_or(Zero::zero(), |p| p.deposit);
+			if data_deposit > old_deposit {
+				T::DepositCurrency::reserve(&who, data_deposit.saturating_sub(old_deposit))?;
+			} else {
+				T::DepositCurrency::unreserve(&who, old_deposit.saturating_sub(data_deposit));
+			}
+			profile.deposit = data_deposit;
+
+			let changed = Self::profile_diff(old_profile.as_ref(), &profile);
+
+			BrandProfileById::<T>::insert(&who, profile);
+
+			Self::deposit_event(Event::BrandProfileUpdated {
+				brand_id: who,
+				changed,
+				deposit: data_deposit,
+			});
+			Ok(())
+		}
+
+		/// Grant `operator` a role on `brand_id`, replacing any role it already had.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 1))]
+		pub fn add_operator(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			operator: T::AccountId,
+			role: BrandRole,
+		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::ensure_brand_role(&brand_id, &who, BrandRole::Owner)?;
+			ensure!(operator != brand_id, Error::<T>::OperatorIsBrand);
+
+			BrandOperators::<T>::insert(&brand_id, &operator, role);
 
-            let brand = BrandById::<T>::get(&who).ok_or(Error::<T>::ThisUserNotCreatedBrandBefore)?;
+			Self::deposit_event(Event::OperatorAdded { brand_id, operator, role });
+			Ok(())
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 1))]
+		pub fn remove_operator(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			operator: T::AccountId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_brand_role(&brand_id, &who, BrandRole::Owner)?;
+			ensure!(
+				BrandOperators::<T>::contains_key(&brand_id, &operator),
+				Error::<T>::OperatorNotFound
+			);
 
-            BrandById::<T>::remove(&who);
+			BrandOperators::<T>::remove(&brand_id, &operator);
 
-			Self::deposit_event(Event::BrandRemoved(who, brand));
+			Self::deposit_event(Event::OperatorRemoved { brand_id, operator });
 			Ok(())
 		}
+
+		/// Propose handing the caller's brand over to `new_owner`. Nothing moves until
+		/// `new_owner` calls `accept_brand_transfer`; a new proposal replaces the previous one.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 1))]
+		pub fn propose_brand_transfer(
+			origin: OriginFor<T>,
+			new_owner: T::AccountId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(BrandById::<T>::contains_key(&who), Error::<T>::ThisUserNotCreatedBrandBefore);
+			ensure!(!WindingDown::<T>::contains_key(&who), Error::<T>::BrandWindingDown);
+			ensure!(!BrandById::<T>::contains_key(&new_owner), Error::<T>::NewOwnerHasBrand);
+
+			PendingBrandTransfer::<T>::insert(&who, &new_owner);
+
+			Self::deposit_event(Event::BrandTransferProposed { brand_id: who, new_owner });
+			Ok(())
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn cancel_brand_transfer(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(PendingBrandTransfer::<T>::contains_key(&who), Error::<T>::NoPendingTransfer);
+
+			PendingBrandTransfer::<T>::remove(&who);
+
+			Self::deposit_event(Event::BrandTransferCancelled { brand_id: who });
+			Ok(())
+		}
+
+		/// Accept a pending transfer of `brand_id`. The brand, its profile, operators, coalition
+		/// proposals and everything the `BrandLifecycle` pallets hold for it are re-keyed to the
+		/// caller, and reserved deposits are moved along.
+		///
+		/// `max_items` bounds the number of per-brand items (operators, outlets, pending proposals
+		/// of its coalition, customer balances, NFTs, ...) to move; the call fails if the brand has
+		/// more, and is only charged for the items actually moved. A brand in no coalition counts
+		/// every coalition instead, as each may hold a join proposal of it.
+		#[pallet::weight(
+			10_000 +
+				T::DbWeight::get().reads_writes(24, 48) +
+				Pallet::<T>::brand_items_weight(*max_items)
+		)]
+		#[transactional]
+		pub fn accept_brand_transfer(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			max_items: u32,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let new_owner =
+				PendingBrandTransfer::<T>::get(&brand_id).ok_or(Error::<T>::NoPendingTransfer)?;
+			ensure!(new_owner == who, Error::<T>::NoPendingTransfer);
+			ensure!(!BrandById::<T>::contains_key(&who), Error::<T>::NewOwnerHasBrand);
+
+			let brand = BrandById::<T>::take(&brand_id).ok_or(Error::<T>::BrandNotFound)?;
+			BrandById::<T>::insert(&who, &brand);
+			BrandIdByName::<T>::insert(Self::name_key(&brand.name), &who);
+			PendingBrandTransfer::<T>::remove(&brand_id);
+
+			BrandStatusOf::<T>::insert(&who, BrandStatusOf::<T>::take(&brand_id));
+
+			let bond = BrandBond::<T>::take(&brand_id);
+			Self::move_reserve(&brand_id, &who, bond)?;
+			BrandBond::<T>::insert(&who, bond);
+
+			if let Some(coalition_id) = CoalitionOf::<T>::take(&brand_id) {
+				Coalitions::<T>::mutate(coalition_id, |coalition| {
+					if let Some(coalition) = coalition {
+						let index = coalition.members.iter().position(|member| member == &brand_id);
+						if let Some(member) = index.and_then(|i| coalition.members.get_mut(i)) {
+							*member = who.clone();
+						}
+					}
+				});
+				CoalitionOf::<T>::insert(&who, coalition_id);
+			}
+
+			if let Some(profile) = BrandProfileById::<T>::take(&brand_id) {
+				Self::move_reserve(&brand_id, &who, profile.deposit)?;
+				BrandProfileById::<T>::insert(&who, profile);
+			}
+
+			let mut budget = max_items;
+			let operators =
+				Self::take_items(BrandOperators::<T>::drain_prefix(&brand_id), &mut budget)?;
+			for (operator, role) in operators {
+				if operator != who {
+					BrandOperators::<T>::insert(&who, operator, role);
+				}
+			}
+
+			let outlets = Self::take_items(Outlets::<T>::drain_prefix(&brand_id), &mut budget)?;
+			for (outlet_id, outlet) in outlets {
+				Outlets::<T>::insert(&who, outlet_id, outlet);
+			}
+			NextOutletId::<T>::insert(&who, NextOutletId::<T>::take(&brand_id));
+
+			if let Some(coalition_id) = CoalitionOf::<T>::get(&who) {
+				let proposals = CoalitionProposals::<T>::drain_prefix(coalition_id);
+				for (proposer, mut proposal) in Self::take_items(proposals, &mut budget)? {
+					let index = proposal.approvals.iter().position(|approver| approver == &brand_id);
+					if let Some(approver) = index.and_then(|i| proposal.approvals.get_mut(i)) {
+						*approver = who.clone();
+					}
+					let proposer = if proposer == brand_id { who.clone() } else { proposer };
+					CoalitionProposals::<T>::insert(coalition_id, proposer, proposal);
+				}
+			} else {
+				// any coalition may hold a join proposal of a brand outside them all
+				let coalitions = Self::take_items(Coalitions::<T>::iter_keys(), &mut budget)?;
+				for coalition_id in coalitions {
+					if let Some(proposal) = CoalitionProposals::<T>::take(coalition_id, &brand_id) {
+						CoalitionProposals::<T>::insert(coalition_id, &who, proposal);
+					}
+				}
+			}
+
+			T::BrandLifecycle::on_brand_transferred(&brand_id, &who, &mut budget)?;
+
+			Self::deposit_event(Event::BrandTransferred { from: brand_id, to: who });
+			let weight = 10_000 +
+				T::DbWeight::get().reads_writes(24, 48) +
+				Self::brand_items_weight(max_items - budget);
+			Ok(Some(weight).into())
+		}
+
+		/// Set the verification status of a brand.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_brand_status(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			status: BrandStatus,
+		) -> DispatchResult {
+			T::VerifierOrigin::ensure_origin(origin)?;
+			ensure!(BrandById::<T>::contains_key(&brand_id), Error::<T>::BrandNotFound);
+
+			BrandStatusOf::<T>::insert(&brand_id, status);
+
+			Self::deposit_event(Event::BrandStatusChanged { brand_id, status });
+			Ok(())
+		}
+
+		/// Slash the registration bond of a suspended brand.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 1))]
+		pub fn slash_brand_deposit(origin: OriginFor<T>, brand_id: T::AccountId) -> DispatchResult {
+			T::VerifierOrigin::ensure_origin(origin)?;
+			ensure!(
+				BrandStatusOf::<T>::get(&brand_id) == BrandStatus::Suspended,
+				Error::<T>::BrandNotSuspended
+			);
+
+			let bond = BrandBond::<T>::take(&brand_id);
+			let (imbalance, remaining) = T::DepositCurrency::slash_reserved(&brand_id, bond);
+			T::Slashed::on_unbalanced(imbalance);
+
+			Self::deposit_event(Event::BrandDepositSlashed {
+				brand_id,
+				amount: bond.saturating_sub(remaining),
+			});
+			Ok(())
+		}
+
+		/// Start a new coalition with the caller's brand as its only member.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 3))]
+		pub fn create_coalition(origin: OriginFor<T>, name: Vec<u8>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_brand_active(&who)?;
+			ensure!(!CoalitionOf::<T>::contains_key(&who), Error::<T>::AlreadyInCoalition);
+			let name = Self::bounded_name(name)?;
+
+			let coalition_id = NextCoalitionId::<T>::get();
+			NextCoalitionId::<T>::put(coalition_id.saturating_add(1));
+
+			let mut members = BoundedVec::default();
+			members.try_push(who.clone()).map_err(|_| Error::<T>::TooManyCoalitionMembers)?;
+			Coalitions::<T>::insert(coalition_id, Coalition { name, members });
+			CoalitionOf::<T>::insert(&who, coalition_id);
+
+			Self::deposit_event(Event::CoalitionCreated {
+				coalition_id,
+				founder: who,
+				account: Self::coalition_account(coalition_id),
+			});
+			Ok(())
+		}
+
+		/// Ask to join a coalition. The caller's brand joins once every member has approved.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 1))]
+		pub fn propose_join(origin: OriginFor<T>, coalition_id: CoalitionId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_brand_active(&who)?;
+			let coalition = Coalitions::<T>::get(coalition_id).ok_or(Error::<T>::CoalitionNotFound)?;
+			ensure!(!CoalitionOf::<T>::contains_key(&who), Error::<T>::AlreadyInCoalition);
+			ensure!(
+				(coalition.members.len() as u32) < T::MaxCoalitionMembers::get(),
+				Error::<T>::TooManyCoalitionMembers
+			);
+
+			Self::propose_change(coalition_id, who, MembershipChange::Join)
+		}
+
+		/// Ask to leave the caller's coalition. The brand leaves once every other member has
+		/// approved.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 1))]
+		pub fn propose_leave(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let coalition_id = CoalitionOf::<T>::get(&who).ok_or(Error::<T>::NotCoalitionMember)?;
+			let coalition = Coalitions::<T>::get(coalition_id).ok_or(Error::<T>::CoalitionNotFound)?;
+			ensure!(coalition.members.len() > 1, Error::<T>::LastCoalitionMember);
+
+			Self::propose_change(coalition_id, who, MembershipChange::Leave)
+		}
+
+		/// Approve the pending join or leave of `brand_id` as a member of `coalition_id`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+		#[transactional]
+		pub fn approve_membership_change(
+			origin: OriginFor<T>,
+			coalition_id: CoalitionId,
+			brand_id: T::AccountId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut coalition =
+				Coalitions::<T>::get(coalition_id).ok_or(Error::<T>::CoalitionNotFound)?;
+			ensure!(coalition.members.contains(&who), Error::<T>::NotCoalitionMember);
+			ensure!(who != brand_id, Error::<T>::AlreadyApproved);
+
+			let mut proposal = CoalitionProposals::<T>::get(coalition_id, &brand_id)
+				.ok_or(Error::<T>::ProposalNotFound)?;
+			ensure!(!proposal.approvals.contains(&who), Error::<T>::AlreadyApproved);
+			proposal.approvals.try_push(who.clone()).map_err(|_| Error::<T>::TooManyCoalitionMembers)?;
+
+			Self::deposit_event(Event::CoalitionChangeApproved {
+				coalition_id,
+				brand_id: brand_id.clone(),
+				approver: who,
+			});
+
+			// approvals of members that have left since do not count
+			let approvals =
+				proposal.approvals.iter().filter(|a| coalition.members.contains(a)).count();
+			let required = match proposal.change {
+				MembershipChange::Join => coalition.members.len(),
+				MembershipChange::Leave => coalition.members.len().saturating_sub(1),
+			};
+			if approvals < required {
+				CoalitionProposals::<T>::insert(coalition_id, &brand_id, proposal);
+				return Ok(())
+			}
+
+			CoalitionProposals::<T>::remove(coalition_id, &brand_id);
+			match proposal.change {
+				MembershipChange::Join => {
+					Self::ensure_brand_active(&brand_id)?;
+					ensure!(
+						!CoalitionOf::<T>::contains_key(&brand_id),
+						Error::<T>::AlreadyInCoalition
+					);
+					coalition
+						.members
+						.try_push(brand_id.clone())
+						.map_err(|_| Error::<T>::TooManyCoalitionMembers)?;
+					CoalitionOf::<T>::insert(&brand_id, coalition_id);
+					Self::deposit_event(Event::CoalitionJoined { coalition_id, brand_id });
+				},
+				MembershipChange::Leave => {
+					coalition.members.retain(|member| member != &brand_id);
+					CoalitionOf::<T>::remove(&brand_id);
+					Self::deposit_event(Event::CoalitionLeft { coalition_id, brand_id });
+				},
+			}
+			Coalitions::<T>::insert(coalition_id, coalition);
+			Ok(())
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 2))]
+		pub fn add_outlet(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			name: Vec<u8>,
+			geohash: Vec<u8>,
+			pos_operator: Option<T::AccountId>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_brand_role(&brand_id, &who, BrandRole::Manager)?;
+			let name = Self::bounded_name(name)?;
+			let geohash = Self::valid_geohash(geohash)?;
+
+			let outlet_id = NextOutletId::<T>::get(&brand_id);
+			NextOutletId::<T>::insert(&brand_id, outlet_id.saturating_add(1));
+			Outlets::<T>::insert(&brand_id, outlet_id, Outlet { name, geohash, pos_operator });
+
+			Self::deposit_event(Event::OutletAdded { brand_id, outlet_id });
+			Ok(())
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 1))]
+		pub fn update_outlet(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			outlet_id: OutletId,
+			name: Vec<u8>,
+			geohash: Vec<u8>,
+			pos_operator: Option<T::AccountId>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_brand_role(&brand_id, &who, BrandRole::Manager)?;
+			let name = Self::bounded_name(name)?;
+			let geohash = Self::valid_geohash(geohash)?;
+			ensure!(Outlets::<T>::contains_key(&brand_id, outlet_id), Error::<T>::OutletNotFound);
+
+			Outlets::<T>::insert(&brand_id, outlet_id, Outlet { name, geohash, pos_operator });
+
+			Self::deposit_event(Event::OutletUpdated { brand_id, outlet_id });
+			Ok(())
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 1))]
+		pub fn remove_outlet(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			outlet_id: OutletId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_brand_role(&brand_id, &who, BrandRole::Manager)?;
+			ensure!(Outlets::<T>::contains_key(&brand_id, outlet_id), Error::<T>::OutletNotFound);
+
+			Outlets::<T>::remove(&brand_id, outlet_id);
+
+			Self::deposit_event(Event::OutletRemoved { brand_id, outlet_id });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Canonical form of a brand name used as the `BrandIdByName` key: ASCII letters are
+		/// lowercased, leading and trailing whitespace is dropped and inner whitespace runs
+		/// collapse to a single space, so `"Coffee  House"` and `" coffee house"` collide.
+		pub fn normalize_name(name: &[u8]) -> Vec<u8> {
+			let mut normalized = Vec::with_capacity(name.len());
+			let mut pending_space = false;
+			for byte in name.iter() {
+				if byte.is_ascii_whitespace() {
+					pending_space = !normalized.is_empty();
+					continue;
+				}
+				if pending_space {
+					normalized.push(b' ');
+					pending_space = false;
+				}
+				normalized.push(byte.to_ascii_lowercase());
+			}
+			normalized
+		}
+
+		fn bounded_name(name: Vec<u8>) -> Result<BrandNameOf<T>, Error<T>> {
+			name.try_into().map_err(|_| Error::<T>::NameTooLong)
+		}
+
+		/// `BrandIdByName` key of a brand name that fits in `MaxNameLen`. Normalizing never
+		/// makes a name longer, so the key always fits as well.
+		pub fn name_key(name: &BrandNameOf<T>) -> BrandNameOf<T> {
+			Self::normalize_name(name).try_into().unwrap_or_default()
+		}
+
+		fn bounded_field(
+			value: Vec<u8>,
+		) -> Result<BoundedVec<u8, T::MaxProfileFieldLen>, Error<T>> {
+			value.try_into().map_err(|_| Error::<T>::ProfileFieldTooLong)
+		}
+
+		fn profile_diff(
+			old: Option<&BrandProfile<DepositBalanceOf<T>, T::MaxProfileFieldLen>>,
+			new: &BrandProfile<DepositBalanceOf<T>, T::MaxProfileFieldLen>,
+		) -> Vec<ProfileField> {
+			let empty = BrandProfile {
+				logo_uri: Default::default(),
+				website: Default::default(),
+				description: Default::default(),
+				contact: Default::default(),
+				category: Default::default(),
+				country: None,
+				deposit: Zero::zero(),
+			};
+			let old = old.unwrap_or(&empty);
+
+			let mut changed = Vec::new();
+			if old.logo_uri != new.logo_uri {
+				changed.push(ProfileField::LogoUri);
+			}
+			if old.website != new.website {
+				changed.push(ProfileField::Website);
+			}
+			if old.description != new.description {
+				changed.push(ProfileField::Description);
+			}
+			if old.contact != new.contact {
+				changed.push(ProfileField::Contact);
+			}
+			if old.category != new.category {
+				changed.push(ProfileField::Category);
+			}
+			if old.country != new.country {
+				changed.push(ProfileField::Country);
+			}
+			changed
+		}
+
+		/// Ensure `who` may act for `brand_id` with at least the `required` role. The brand
+		/// account itself always passes.
+		pub fn ensure_brand_role(
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			required: BrandRole,
+		) -> DispatchResult {
+			ensure!(BrandById::<T>::contains_key(brand_id), Error::<T>::BrandNotFound);
+			if who == brand_id {
+				return Ok(())
+			}
+			let role =
+				BrandOperators::<T>::get(brand_id, who).ok_or(Error::<T>::NotBrandOperator)?;
+			ensure!(role.allows(required), Error::<T>::NotBrandOperator);
+			Ok(())
+		}
+
+		fn do_remove_brand(who: &T::AccountId, budget: &mut u32) -> DispatchResult {
+			let brand = BrandById::<T>::take(who).ok_or(Error::<T>::BrandNotFound)?;
+
+			T::BrandLifecycle::on_brand_removed(who, budget)?;
+
+			BrandIdByName::<T>::remove(Self::name_key(&brand.name));
+			if let Some(profile) = BrandProfileById::<T>::take(who) {
+				T::DepositCurrency::unreserve(who, profile.deposit);
+			}
+			Self::take_items(BrandOperators::<T>::drain_prefix(who), budget)?;
+			Self::take_items(Outlets::<T>::drain_prefix(who), budget)?;
+			NextOutletId::<T>::remove(who);
+			PendingBrandTransfer::<T>::remove(who);
+			WindingDown::<T>::remove(who);
+			BrandStatusOf::<T>::remove(who);
+			T::DepositCurrency::unreserve(who, BrandBond::<T>::take(who));
+			if let Some(coalition_id) = CoalitionOf::<T>::take(who) {
+				Coalitions::<T>::mutate(coalition_id, |coalition| {
+					if let Some(coalition) = coalition {
+						coalition.members.retain(|member| member != who);
+					}
+				});
+				CoalitionProposals::<T>::remove(coalition_id, who);
+			}
+
+			Self::deposit_event(Event::BrandRemoved(who.clone(), brand));
+			Ok(())
+		}
+
+		/// Ensure the brand exists, is not suspended and is not winding down, i.e. may take on
+		/// new obligations such as minting or issuing points.
+		pub fn ensure_brand_active(brand_id: &T::AccountId) -> DispatchResult {
+			ensure!(BrandById::<T>::contains_key(brand_id), Error::<T>::BrandNotFound);
+			Self::ensure_not_suspended(brand_id)?;
+			ensure!(!WindingDown::<T>::contains_key(brand_id), Error::<T>::BrandWindingDown);
+			Ok(())
+		}
+
+		/// Ensure the brand has not been suspended by `VerifierOrigin`.
+		pub fn ensure_not_suspended(brand_id: &T::AccountId) -> DispatchResult {
+			ensure!(
+				BrandStatusOf::<T>::get(brand_id) != BrandStatus::Suspended,
+				Error::<T>::BrandSuspended
+			);
+			Ok(())
+		}
+
+		/// Weight of moving or removing `items` per-brand items, each read, written and possibly
+		/// carrying a reserve along.
+		pub fn brand_items_weight(items: u32) -> Weight {
+			T::DbWeight::get().reads_writes(3, 5).saturating_mul(items.into())
+		}
+
+		/// Count one more per-brand item against `budget`, failing once it is used up.
+		pub fn use_item(budget: &mut u32) -> DispatchResult {
+			*budget = budget.checked_sub(1).ok_or(Error::<T>::TooManyBrandItems)?;
+			Ok(())
+		}
+
+		/// Collect `items`, counting each against `budget`. Meant for `drain_prefix`, which then
+		/// never removes more than `budget` entries.
+		pub fn take_items<I: Iterator>(
+			items: I,
+			budget: &mut u32,
+		) -> Result<Vec<I::Item>, DispatchError> {
+			items.map(|item| Self::use_item(budget).map(|_| item)).collect()
+		}
+
+		fn move_reserve(
+			from: &T::AccountId,
+			to: &T::AccountId,
+			amount: DepositBalanceOf<T>,
+		) -> DispatchResult {
+			let remaining =
+				T::DepositCurrency::repatriate_reserved(from, to, amount, BalanceStatus::Reserved)?;
+			ensure!(remaining.is_zero(), Error::<T>::DepositNotMoved);
+			Ok(())
+		}
+
+		fn propose_change(
+			coalition_id: CoalitionId,
+			brand_id: T::AccountId,
+			change: MembershipChange,
+		) -> DispatchResult {
+			ensure!(
+				!CoalitionProposals::<T>::contains_key(coalition_id, &brand_id),
+				Error::<T>::ProposalExists
+			);
+
+			CoalitionProposals::<T>::insert(
+				coalition_id,
+				&brand_id,
+				MembershipProposal { change, approvals: BoundedVec::default() },
+			);
+
+			Self::deposit_event(Event::CoalitionChangeProposed { coalition_id, brand_id, change });
+			Ok(())
+		}
+
+		/// The account holding the shared point pool of a coalition.
+		pub fn coalition_account(coalition_id: CoalitionId) -> T::AccountId {
+			T::CoalitionPalletId::get().into_sub_account(coalition_id)
+		}
+
+		/// Ensure `brand_id` is currently a member of `coalition_id`.
+		pub fn ensure_coalition_member(
+			coalition_id: CoalitionId,
+			brand_id: &T::AccountId,
+		) -> DispatchResult {
+			ensure!(
+				CoalitionOf::<T>::get(brand_id) == Some(coalition_id),
+				Error::<T>::NotCoalitionMember
+			);
+			Ok(())
+		}
+
+		fn valid_geohash(geohash: Vec<u8>) -> Result<BoundedVec<u8, MaxGeohashLen>, Error<T>> {
+			const ALPHABET: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+			ensure!(
+				!geohash.is_empty() && geohash.iter().all(|c| ALPHABET.contains(c)),
+				Error::<T>::InvalidGeohash
+			);
+			geohash.try_into().map_err(|_| Error::<T>::InvalidGeohash)
+		}
+
+		/// Ensure `outlet_id` is an outlet of `brand_id`.
+		pub fn ensure_outlet(brand_id: &T::AccountId, outlet_id: OutletId) -> DispatchResult {
+			ensure!(Outlets::<T>::contains_key(brand_id, outlet_id), Error::<T>::OutletNotFound);
+			Ok(())
+		}
+
+		/// Like [`Self::ensure_brand_role`], but also lets the POS operator of `outlet` through.
+		pub fn ensure_brand_role_at_outlet(
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			required: BrandRole,
+			outlet: Option<OutletId>,
+		) -> DispatchResult {
+			if let Some(outlet_id) = outlet {
+				let outlet =
+					Outlets::<T>::get(brand_id, outlet_id).ok_or(Error::<T>::OutletNotFound)?;
+				if outlet.pos_operator.as_ref() == Some(who) {
+					return Ok(())
+				}
+			}
+			Self::ensure_brand_role(brand_id, who, required)
+		}
+
+		/// Resolve a brand by its display name, ignoring case and whitespace differences.
+		pub fn brand_by_name(name: &[u8]) -> Option<(T::AccountId, Brand<T::MaxNameLen>)> {
+			let key: BrandNameOf<T> = Self::normalize_name(name).try_into().ok()?;
+			let brand_id = BrandIdByName::<T>::get(key)?;
+			let brand = BrandById::<T>::get(&brand_id)?;
+			Some((brand_id, brand))
+		}
 	}
 }
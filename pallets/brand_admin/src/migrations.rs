@@ -0,0 +1,160 @@
+//! Storage migrations for the brand admin pallet.
+
+pub mod v1 {
+	use crate::{BrandById, BrandIdByName, Config, Pallet};
+	use frame_support::{
+		traits::{Get, GetStorageVersion, StorageVersion},
+		weights::Weight,
+	};
+
+	/// Build the `BrandIdByName` index from the existing `BrandById` entries.
+	///
+	/// Brands registered before the index existed may only differ by case or whitespace; the
+	/// first one found keeps the name and the others stay reachable through `BrandById` only.
+	pub fn migrate<T: Config>() -> Weight {
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+		if on_chain_version >= 1 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut reads: Weight = 1;
+		let mut writes: Weight = 1;
+		for (brand_id, brand) in BrandById::<T>::iter() {
+			reads += 2;
+			let key = Pallet::<T>::name_key(&brand.name);
+			if key.is_empty() || BrandIdByName::<T>::contains_key(&key) {
+				continue
+			}
+			BrandIdByName::<T>::insert(key, brand_id);
+			writes += 1;
+		}
+
+		StorageVersion::new(1).put::<Pallet<T>>();
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+}
+
+pub mod v2 {
+	use crate::{
+		Brand, BrandById, BrandIdByName, Coalition, CoalitionOf, CoalitionProposals, Coalitions,
+		Config, MembershipChange, MembershipProposal, Outlet, Outlets, Pallet,
+	};
+	use codec::{Decode, Encode};
+	use frame_support::{
+		storage::{unhashed, StoragePrefixedMap},
+		traits::{Get, GetStorageVersion, StorageVersion},
+		weights::Weight,
+		BoundedVec, Blake2_128Concat, StorageHasher,
+	};
+	use sp_std::vec::Vec;
+
+	#[derive(Decode)]
+	struct OldBrand {
+		name: Vec<u8>,
+	}
+
+	#[derive(Decode)]
+	struct OldCoalition<AccountId> {
+		name: Vec<u8>,
+		members: Vec<AccountId>,
+	}
+
+	#[derive(Decode)]
+	struct OldMembershipProposal<AccountId> {
+		change: MembershipChange,
+		approvals: Vec<AccountId>,
+	}
+
+	#[derive(Decode)]
+	struct OldOutlet<AccountId> {
+		name: Vec<u8>,
+		geohash: Vec<u8>,
+		pos_operator: Option<AccountId>,
+	}
+
+	fn truncate<Item, S: Get<u32>>(mut items: Vec<Item>) -> BoundedVec<Item, S> {
+		items.truncate(S::get() as usize);
+		items.try_into().unwrap_or_default()
+	}
+
+	/// Move names, coalitions and outlets to bounded storage.
+	///
+	/// Oversized names are truncated to `MaxNameLen` and re-indexed in `BrandIdByName` when the
+	/// truncated name is still free. Coalitions over `MaxCoalitionMembers` keep their earliest
+	/// members; the others are released from the coalition.
+	pub fn migrate<T: Config>() -> Weight {
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+		if on_chain_version >= 2 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let max_name_len = T::MaxNameLen::get() as usize;
+		let mut reads: Weight = 1;
+		let mut writes: Weight = 1;
+
+		let mut truncated = Vec::new();
+		BrandById::<T>::translate::<OldBrand, _>(|brand_id, old| {
+			reads += 1;
+			writes += 1;
+			if old.name.len() > max_name_len {
+				// the old index key no longer decodes, drop it by its raw storage key
+				let mut old_key = BrandIdByName::<T>::final_prefix().to_vec();
+				old_key.extend(Blake2_128Concat::hash(
+					&Pallet::<T>::normalize_name(&old.name).encode(),
+				));
+				unhashed::kill(&old_key);
+				writes += 1;
+
+				let brand = Brand { name: truncate(old.name) };
+				truncated.push((brand_id, brand.name.clone()));
+				Some(brand)
+			} else {
+				Some(Brand { name: truncate(old.name) })
+			}
+		});
+		for (brand_id, name) in truncated {
+			reads += 1;
+			let key = Pallet::<T>::name_key(&name);
+			if !key.is_empty() && !BrandIdByName::<T>::contains_key(&key) {
+				BrandIdByName::<T>::insert(key, brand_id);
+				writes += 1;
+			}
+		}
+
+		let mut released = Vec::new();
+		Coalitions::<T>::translate::<OldCoalition<T::AccountId>, _>(|_, old| {
+			reads += 1;
+			writes += 1;
+			let mut members = old.members;
+			if members.len() > T::MaxCoalitionMembers::get() as usize {
+				released.extend(members.split_off(T::MaxCoalitionMembers::get() as usize));
+			}
+			Some(Coalition { name: truncate(old.name), members: truncate(members) })
+		});
+		for brand_id in released {
+			CoalitionOf::<T>::remove(brand_id);
+			writes += 1;
+		}
+
+		CoalitionProposals::<T>::translate::<OldMembershipProposal<T::AccountId>, _>(
+			|_, _, old| {
+				reads += 1;
+				writes += 1;
+				Some(MembershipProposal { change: old.change, approvals: truncate(old.approvals) })
+			},
+		);
+
+		Outlets::<T>::translate::<OldOutlet<T::AccountId>, _>(|_, _, old| {
+			reads += 1;
+			writes += 1;
+			Some(Outlet {
+				name: truncate(old.name),
+				geohash: truncate(old.geohash),
+				pos_operator: old.pos_operator,
+			})
+		});
+
+		StorageVersion::new(2).put::<Pallet<T>>();
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+}
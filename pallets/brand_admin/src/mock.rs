@@ -0,0 +1,144 @@
+use crate as pallet_brand_admin;
+use crate::BrandLifecycle;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64},
+	PalletId,
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	DispatchResult,
+};
+use std::cell::RefCell;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub const BRAND_DEPOSIT: u64 = 100;
+pub const WIND_DOWN_PERIOD: u64 = 10;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		BrandAdmin: pallet_brand_admin::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+thread_local! {
+	static LIVE_ASSETS: RefCell<bool> = RefCell::new(false);
+	static BRAND_ITEMS: RefCell<u32> = RefCell::new(0);
+}
+
+/// Make the mock lifecycle pallet report live assets for every brand.
+pub fn set_live_assets(live: bool) {
+	LIVE_ASSETS.with(|v| *v.borrow_mut() = live);
+}
+
+/// Make the mock lifecycle pallet hold `items` per-brand items for every brand.
+pub fn set_brand_items(items: u32) {
+	BRAND_ITEMS.with(|v| *v.borrow_mut() = items);
+}
+
+/// Stands in for the pallets keeping per-brand state.
+pub struct MockLifecycle;
+
+impl MockLifecycle {
+	fn use_items(budget: &mut u32) -> DispatchResult {
+		for _ in 0..BRAND_ITEMS.with(|v| *v.borrow()) {
+			BrandAdmin::use_item(budget)?;
+		}
+		Ok(())
+	}
+}
+
+impl BrandLifecycle<u64> for MockLifecycle {
+	fn on_brand_transferred(_from: &u64, _to: &u64, budget: &mut u32) -> DispatchResult {
+		Self::use_items(budget)
+	}
+
+	fn has_live_assets(_brand: &u64) -> bool {
+		LIVE_ASSETS.with(|v| *v.borrow())
+	}
+
+	fn on_brand_removed(_brand: &u64, budget: &mut u32) -> DispatchResult {
+		Self::use_items(budget)
+	}
+}
+
+parameter_types! {
+	pub const CoalitionPalletId: PalletId = PalletId(*b"loy/coal");
+}
+
+impl pallet_brand_admin::Config for Test {
+	type Event = Event;
+	type DepositCurrency = Balances;
+	type ProfileDepositPerByte = ConstU64<1>;
+	type MaxNameLen = ConstU32<16>;
+	type MaxProfileFieldLen = ConstU32<32>;
+	type BrandLifecycle = MockLifecycle;
+	type WindDownPeriod = ConstU64<WIND_DOWN_PERIOD>;
+	type VerifierOrigin = frame_system::EnsureRoot<u64>;
+	type BrandDeposit = ConstU64<BRAND_DEPOSIT>;
+	type Slashed = ();
+	type CoalitionPalletId = CoalitionPalletId;
+	type MaxCoalitionMembers = ConstU32<3>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: (1..=6).map(|who| (who, 1_000)).collect() }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,772 @@
+use crate::{
+	mock::*, BrandBond, BrandById, BrandIdByName, BrandOperators, BrandRole, BrandStatus,
+	CoalitionOf, CoalitionProposals, Coalitions, Error, Event as BrandAdminEvent, MembershipChange,
+	Outlets, PendingBrandTransfer, ProfileField, RemovalMode, WindingDown,
+};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::{unhashed, StoragePrefixedMap},
+	traits::{GetStorageVersion, StorageVersion},
+	Blake2_128Concat, BoundedVec, StorageHasher,
+};
+use sp_runtime::DispatchError;
+
+fn create_brand(who: u64, name: &[u8]) {
+	assert_ok!(BrandAdmin::create_brand(Origin::signed(who), name.to_vec()));
+}
+
+fn bounded_name(name: &[u8]) -> BoundedVec<u8, <Test as crate::Config>::MaxNameLen> {
+	name.to_vec().try_into().unwrap()
+}
+
+#[test]
+fn create_brand_indexes_the_normalized_name() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee  House");
+
+		assert!(BrandById::<Test>::contains_key(1));
+		assert_eq!(BrandIdByName::<Test>::get(bounded_name(b"coffee house")), Some(1));
+		assert_eq!(BrandAdmin::brand_by_name(b" COFFEE house ").map(|(id, _)| id), Some(1));
+	});
+}
+
+#[test]
+fn create_brand_rejects_taken_empty_and_long_names() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+
+		assert_noop!(
+			BrandAdmin::create_brand(Origin::signed(2), b" coffee   HOUSE".to_vec()),
+			Error::<Test>::BrandNameExisted
+		);
+		assert_noop!(
+			BrandAdmin::create_brand(Origin::signed(2), b"   ".to_vec()),
+			Error::<Test>::InvalidBrandName
+		);
+		assert_noop!(
+			BrandAdmin::create_brand(Origin::signed(2), [b'a'; 17].to_vec()),
+			Error::<Test>::NameTooLong
+		);
+	});
+}
+
+#[test]
+fn renaming_a_brand_frees_the_old_name() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		create_brand(1, b"Tea House");
+
+		assert_eq!(BrandIdByName::<Test>::get(bounded_name(b"coffee house")), None);
+		assert_eq!(BrandIdByName::<Test>::get(bounded_name(b"tea house")), Some(1));
+		create_brand(2, b"Coffee House");
+	});
+}
+
+#[test]
+fn strict_removal_drops_the_brand_and_its_name() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::Strict, 0));
+
+		assert!(!BrandById::<Test>::contains_key(1));
+		assert_eq!(BrandIdByName::<Test>::get(bounded_name(b"coffee house")), None);
+	});
+}
+
+#[test]
+fn strict_removal_fails_with_live_assets() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		set_live_assets(true);
+
+		assert_noop!(
+			BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::Strict, 0),
+			Error::<Test>::BrandHasLiveAssets
+		);
+		assert_noop!(
+			BrandAdmin::remove_brand(Origin::signed(2), RemovalMode::Strict, 0),
+			Error::<Test>::ThisUserNotCreatedBrandBefore
+		);
+	});
+}
+
+#[test]
+fn removal_is_bounded_by_max_items() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		assert_ok!(BrandAdmin::add_operator(Origin::signed(1), 1, 2, BrandRole::Cashier));
+		set_brand_items(2);
+
+		// two lifecycle items and one operator
+		assert_noop!(
+			BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::Strict, 2),
+			Error::<Test>::TooManyBrandItems
+		);
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::Strict, 3));
+		assert_eq!(BrandOperators::<Test>::get(1, 2), None);
+	});
+}
+
+#[test]
+fn wind_down_then_finalize() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		set_live_assets(true);
+
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::WindDown, 0));
+		let ends_at = 1 + WIND_DOWN_PERIOD;
+		assert_eq!(WindingDown::<Test>::get(1), Some(ends_at));
+		System::assert_last_event(
+			BrandAdminEvent::BrandWindDownStarted { brand_id: 1, ends_at }.into(),
+		);
+		assert_noop!(
+			BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::WindDown, 0),
+			Error::<Test>::BrandWindingDown
+		);
+		assert_noop!(
+			BrandAdmin::propose_brand_transfer(Origin::signed(1), 2),
+			Error::<Test>::BrandWindingDown
+		);
+		assert_noop!(BrandAdmin::ensure_brand_active(&1), Error::<Test>::BrandWindingDown);
+
+		System::set_block_number(ends_at - 1);
+		assert_noop!(
+			BrandAdmin::finalize_brand_removal(Origin::signed(3), 1, 0),
+			Error::<Test>::WindDownNotOver
+		);
+
+		System::set_block_number(ends_at);
+		assert_ok!(BrandAdmin::finalize_brand_removal(Origin::signed(3), 1, 0));
+		assert!(!BrandById::<Test>::contains_key(1));
+		assert_eq!(WindingDown::<Test>::get(1), None);
+	});
+}
+
+#[test]
+fn finalize_requires_wind_down() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+
+		assert_noop!(
+			BrandAdmin::finalize_brand_removal(Origin::signed(3), 1, 0),
+			Error::<Test>::BrandNotWindingDown
+		);
+	});
+}
+
+#[test]
+fn brand_bond_follows_the_brand() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		assert_eq!(BrandBond::<Test>::get(1), BRAND_DEPOSIT);
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT);
+
+		create_brand(1, b"Tea House");
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT);
+
+		assert_ok!(BrandAdmin::propose_brand_transfer(Origin::signed(1), 2));
+		assert_ok!(BrandAdmin::accept_brand_transfer(Origin::signed(2), 1, 10));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), BRAND_DEPOSIT);
+		assert_eq!(BrandBond::<Test>::get(2), BRAND_DEPOSIT);
+
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(2), RemovalMode::Strict, 0));
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(BrandBond::<Test>::get(2), 0);
+	});
+}
+
+#[test]
+fn suspended_brands_cannot_be_removed() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::WindDown, 0));
+		create_brand(2, b"Tea House");
+		assert_ok!(BrandAdmin::set_brand_status(Origin::root(), 1, BrandStatus::Suspended));
+		assert_ok!(BrandAdmin::set_brand_status(Origin::root(), 2, BrandStatus::Suspended));
+
+		for mode in [RemovalMode::Strict, RemovalMode::WindDown] {
+			assert_noop!(
+				BrandAdmin::remove_brand(Origin::signed(2), mode, 0),
+				Error::<Test>::BrandSuspended
+			);
+		}
+		System::set_block_number(1 + WIND_DOWN_PERIOD);
+		assert_noop!(
+			BrandAdmin::finalize_brand_removal(Origin::signed(3), 1, 0),
+			Error::<Test>::BrandSuspended
+		);
+	});
+}
+
+#[test]
+fn set_brand_status_requires_verifier() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+
+		assert_noop!(
+			BrandAdmin::set_brand_status(Origin::signed(1), 1, BrandStatus::Verified),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			BrandAdmin::set_brand_status(Origin::root(), 2, BrandStatus::Verified),
+			Error::<Test>::BrandNotFound
+		);
+		assert_ok!(BrandAdmin::set_brand_status(Origin::root(), 1, BrandStatus::Verified));
+		assert_eq!(BrandAdmin::brand_status(1), BrandStatus::Verified);
+	});
+}
+
+#[test]
+fn slash_brand_deposit_only_when_suspended() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+
+		assert_noop!(
+			BrandAdmin::slash_brand_deposit(Origin::root(), 1),
+			Error::<Test>::BrandNotSuspended
+		);
+		assert_ok!(BrandAdmin::set_brand_status(Origin::root(), 1, BrandStatus::Suspended));
+		assert_ok!(BrandAdmin::slash_brand_deposit(Origin::root(), 1));
+
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(1), 1_000 - BRAND_DEPOSIT);
+		assert_eq!(BrandBond::<Test>::get(1), 0);
+		System::assert_last_event(
+			BrandAdminEvent::BrandDepositSlashed { brand_id: 1, amount: BRAND_DEPOSIT }.into(),
+		);
+	});
+}
+
+#[test]
+fn update_brand_profile_adjusts_deposit() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+
+		assert_ok!(BrandAdmin::update_brand_profile(
+			Origin::signed(1),
+			b"logo".to_vec(),
+			b"web".to_vec(),
+			Vec::new(),
+			Vec::new(),
+			Vec::new(),
+			Some(*b"DE"),
+		));
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT + 9);
+		System::assert_last_event(
+			BrandAdminEvent::BrandProfileUpdated {
+				brand_id: 1,
+				changed: vec![ProfileField::LogoUri, ProfileField::Website, ProfileField::Country],
+				deposit: 9,
+			}
+			.into(),
+		);
+
+		assert_ok!(BrandAdmin::update_brand_profile(
+			Origin::signed(1),
+			b"logo".to_vec(),
+			Vec::new(),
+			Vec::new(),
+			Vec::new(),
+			Vec::new(),
+			Some(*b"DE"),
+		));
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT + 6);
+		System::assert_last_event(
+			BrandAdminEvent::BrandProfileUpdated {
+				brand_id: 1,
+				changed: vec![ProfileField::Website],
+				deposit: 6,
+			}
+			.into(),
+		);
+	});
+}
+
+#[test]
+fn update_brand_profile_errors() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			BrandAdmin::update_brand_profile(
+				Origin::signed(1),
+				Vec::new(),
+				Vec::new(),
+				Vec::new(),
+				Vec::new(),
+				Vec::new(),
+				None,
+			),
+			Error::<Test>::ThisUserNotCreatedBrandBefore
+		);
+
+		create_brand(1, b"Coffee House");
+		assert_noop!(
+			BrandAdmin::update_brand_profile(
+				Origin::signed(1),
+				[b'a'; 33].to_vec(),
+				Vec::new(),
+				Vec::new(),
+				Vec::new(),
+				Vec::new(),
+				None,
+			),
+			Error::<Test>::ProfileFieldTooLong
+		);
+	});
+}
+
+#[test]
+fn operators_act_within_their_role() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		assert_ok!(BrandAdmin::add_operator(Origin::signed(1), 1, 2, BrandRole::Cashier));
+		assert_ok!(BrandAdmin::add_operator(Origin::signed(1), 1, 3, BrandRole::Manager));
+
+		assert_ok!(BrandAdmin::ensure_brand_role(&1, &1, BrandRole::Owner));
+		assert_ok!(BrandAdmin::ensure_brand_role(&1, &2, BrandRole::Cashier));
+		assert_noop!(
+			BrandAdmin::ensure_brand_role(&1, &2, BrandRole::Minter),
+			Error::<Test>::NotBrandOperator
+		);
+		assert_ok!(BrandAdmin::ensure_brand_role(&1, &3, BrandRole::Minter));
+		assert_noop!(
+			BrandAdmin::ensure_brand_role(&1, &3, BrandRole::Owner),
+			Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			BrandAdmin::ensure_brand_role(&1, &4, BrandRole::Cashier),
+			Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			BrandAdmin::ensure_brand_role(&5, &5, BrandRole::Cashier),
+			Error::<Test>::BrandNotFound
+		);
+	});
+}
+
+#[test]
+fn operator_management_errors() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		assert_ok!(BrandAdmin::add_operator(Origin::signed(1), 1, 3, BrandRole::Manager));
+
+		assert_noop!(
+			BrandAdmin::add_operator(Origin::signed(3), 1, 2, BrandRole::Cashier),
+			Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			BrandAdmin::add_operator(Origin::signed(1), 1, 1, BrandRole::Cashier),
+			Error::<Test>::OperatorIsBrand
+		);
+		assert_noop!(
+			BrandAdmin::remove_operator(Origin::signed(1), 1, 2),
+			Error::<Test>::OperatorNotFound
+		);
+
+		assert_ok!(BrandAdmin::remove_operator(Origin::signed(1), 1, 3));
+		assert_eq!(BrandOperators::<Test>::get(1, 3), None);
+	});
+}
+
+#[test]
+fn brand_transfer_moves_brand_operators_and_outlets() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		assert_ok!(BrandAdmin::add_operator(Origin::signed(1), 1, 3, BrandRole::Cashier));
+		assert_ok!(BrandAdmin::add_operator(Origin::signed(1), 1, 2, BrandRole::Manager));
+		assert_ok!(BrandAdmin::add_outlet(
+			Origin::signed(1),
+			1,
+			b"Main".to_vec(),
+			b"u33d".to_vec(),
+			None
+		));
+		assert_ok!(BrandAdmin::create_coalition(Origin::signed(1), b"Mall".to_vec()));
+
+		assert_ok!(BrandAdmin::propose_brand_transfer(Origin::signed(1), 2));
+		assert_eq!(PendingBrandTransfer::<Test>::get(1), Some(2));
+		assert_noop!(
+			BrandAdmin::accept_brand_transfer(Origin::signed(3), 1, 10),
+			Error::<Test>::NoPendingTransfer
+		);
+		assert_ok!(BrandAdmin::accept_brand_transfer(Origin::signed(2), 1, 10));
+
+		assert!(!BrandById::<Test>::contains_key(1));
+		assert!(BrandById::<Test>::contains_key(2));
+		assert_eq!(BrandIdByName::<Test>::get(bounded_name(b"coffee house")), Some(2));
+		// the new owner is the brand itself now, not an operator
+		assert_eq!(BrandOperators::<Test>::get(2, 2), None);
+		assert_eq!(BrandOperators::<Test>::get(2, 3), Some(BrandRole::Cashier));
+		assert!(Outlets::<Test>::contains_key(2, 0));
+		assert!(!Outlets::<Test>::contains_key(1, 0));
+		assert_eq!(CoalitionOf::<Test>::get(2), Some(0));
+		assert_eq!(CoalitionOf::<Test>::get(1), None);
+		assert_eq!(Coalitions::<Test>::get(0).unwrap().members.into_inner(), vec![2]);
+		System::assert_last_event(BrandAdminEvent::BrandTransferred { from: 1, to: 2 }.into());
+	});
+}
+
+#[test]
+fn brand_transfer_is_bounded_by_max_items() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		assert_ok!(BrandAdmin::add_operator(Origin::signed(1), 1, 3, BrandRole::Cashier));
+		set_brand_items(2);
+		assert_ok!(BrandAdmin::propose_brand_transfer(Origin::signed(1), 2));
+
+		assert_noop!(
+			BrandAdmin::accept_brand_transfer(Origin::signed(2), 1, 2),
+			Error::<Test>::TooManyBrandItems
+		);
+		assert_ok!(BrandAdmin::accept_brand_transfer(Origin::signed(2), 1, 3));
+	});
+}
+
+#[test]
+fn brand_transfer_re_keys_coalition_proposals() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		create_brand(2, b"Tea House");
+		create_brand(3, b"Juice Bar");
+		assert_ok!(BrandAdmin::create_coalition(Origin::signed(1), b"Mall".to_vec()));
+		assert_ok!(BrandAdmin::propose_join(Origin::signed(2), 0));
+		assert_ok!(BrandAdmin::approve_membership_change(Origin::signed(1), 0, 2));
+		assert_ok!(BrandAdmin::propose_join(Origin::signed(3), 0));
+		assert_ok!(BrandAdmin::approve_membership_change(Origin::signed(1), 0, 3));
+		assert_ok!(BrandAdmin::propose_leave(Origin::signed(1)));
+
+		// a member: its own proposal and its approvals follow it
+		assert_ok!(BrandAdmin::propose_brand_transfer(Origin::signed(1), 4));
+		assert_ok!(BrandAdmin::accept_brand_transfer(Origin::signed(4), 1, 10));
+		assert!(!CoalitionProposals::<Test>::contains_key(0, 1));
+		assert_eq!(CoalitionProposals::<Test>::get(0, 4).unwrap().change, MembershipChange::Leave);
+		assert_eq!(CoalitionProposals::<Test>::get(0, 3).unwrap().approvals.into_inner(), vec![4]);
+
+		// not a member yet: its join proposal follows it
+		assert_ok!(BrandAdmin::propose_brand_transfer(Origin::signed(3), 5));
+		assert_ok!(BrandAdmin::accept_brand_transfer(Origin::signed(5), 3, 10));
+		assert!(!CoalitionProposals::<Test>::contains_key(0, 3));
+		assert_ok!(BrandAdmin::approve_membership_change(Origin::signed(2), 0, 5));
+		assert_eq!(CoalitionOf::<Test>::get(5), Some(0));
+	});
+}
+
+#[test]
+fn brand_transfer_errors() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		create_brand(2, b"Tea House");
+
+		assert_noop!(
+			BrandAdmin::propose_brand_transfer(Origin::signed(3), 4),
+			Error::<Test>::ThisUserNotCreatedBrandBefore
+		);
+		assert_noop!(
+			BrandAdmin::propose_brand_transfer(Origin::signed(1), 2),
+			Error::<Test>::NewOwnerHasBrand
+		);
+		assert_noop!(
+			BrandAdmin::cancel_brand_transfer(Origin::signed(1)),
+			Error::<Test>::NoPendingTransfer
+		);
+
+		assert_ok!(BrandAdmin::propose_brand_transfer(Origin::signed(1), 3));
+		assert_ok!(BrandAdmin::cancel_brand_transfer(Origin::signed(1)));
+		assert_noop!(
+			BrandAdmin::accept_brand_transfer(Origin::signed(3), 1, 10),
+			Error::<Test>::NoPendingTransfer
+		);
+
+		// the new owner registered a brand of their own meanwhile
+		assert_ok!(BrandAdmin::propose_brand_transfer(Origin::signed(1), 3));
+		create_brand(3, b"Juice Bar");
+		assert_noop!(
+			BrandAdmin::accept_brand_transfer(Origin::signed(3), 1, 10),
+			Error::<Test>::NewOwnerHasBrand
+		);
+	});
+}
+
+#[test]
+fn coalition_join_needs_every_member() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		create_brand(2, b"Tea House");
+		create_brand(3, b"Juice Bar");
+		assert_ok!(BrandAdmin::create_coalition(Origin::signed(1), b"Mall".to_vec()));
+		System::assert_last_event(
+			BrandAdminEvent::CoalitionCreated {
+				coalition_id: 0,
+				founder: 1,
+				account: BrandAdmin::coalition_account(0),
+			}
+			.into(),
+		);
+
+		assert_ok!(BrandAdmin::propose_join(Origin::signed(2), 0));
+		assert_ok!(BrandAdmin::approve_membership_change(Origin::signed(1), 0, 2));
+		assert_eq!(CoalitionOf::<Test>::get(2), Some(0));
+
+		assert_ok!(BrandAdmin::propose_join(Origin::signed(3), 0));
+		assert_ok!(BrandAdmin::approve_membership_change(Origin::signed(1), 0, 3));
+		assert_eq!(CoalitionOf::<Test>::get(3), None);
+		assert_ok!(BrandAdmin::approve_membership_change(Origin::signed(2), 0, 3));
+		assert_eq!(CoalitionOf::<Test>::get(3), Some(0));
+		assert_eq!(Coalitions::<Test>::get(0).unwrap().members.into_inner(), vec![1, 2, 3]);
+		assert_ok!(BrandAdmin::ensure_coalition_member(0, &3));
+	});
+}
+
+#[test]
+fn coalition_join_errors() {
+	new_test_ext().execute_with(|| {
+		for (who, name) in [(1, b"A"), (2, b"B"), (3, b"C"), (4, b"D")] {
+			create_brand(who, name);
+		}
+		assert_ok!(BrandAdmin::create_coalition(Origin::signed(1), b"Mall".to_vec()));
+
+		assert_noop!(
+			BrandAdmin::propose_join(Origin::signed(2), 1),
+			Error::<Test>::CoalitionNotFound
+		);
+		assert_noop!(BrandAdmin::propose_join(Origin::signed(5), 0), Error::<Test>::BrandNotFound);
+		assert_noop!(
+			BrandAdmin::create_coalition(Origin::signed(1), b"Other".to_vec()),
+			Error::<Test>::AlreadyInCoalition
+		);
+		assert_noop!(
+			BrandAdmin::approve_membership_change(Origin::signed(1), 0, 2),
+			Error::<Test>::ProposalNotFound
+		);
+
+		assert_ok!(BrandAdmin::propose_join(Origin::signed(2), 0));
+		assert_noop!(BrandAdmin::propose_join(Origin::signed(2), 0), Error::<Test>::ProposalExists);
+		assert_noop!(
+			BrandAdmin::approve_membership_change(Origin::signed(2), 0, 2),
+			Error::<Test>::NotCoalitionMember
+		);
+		assert_noop!(
+			BrandAdmin::approve_membership_change(Origin::signed(3), 0, 2),
+			Error::<Test>::NotCoalitionMember
+		);
+		assert_ok!(BrandAdmin::approve_membership_change(Origin::signed(1), 0, 2));
+
+		assert_ok!(BrandAdmin::propose_join(Origin::signed(3), 0));
+		assert_ok!(BrandAdmin::approve_membership_change(Origin::signed(1), 0, 3));
+		assert_noop!(
+			BrandAdmin::approve_membership_change(Origin::signed(1), 0, 3),
+			Error::<Test>::AlreadyApproved
+		);
+		assert_ok!(BrandAdmin::approve_membership_change(Origin::signed(2), 0, 3));
+
+		assert_noop!(
+			BrandAdmin::propose_join(Origin::signed(4), 0),
+			Error::<Test>::TooManyCoalitionMembers
+		);
+	});
+}
+
+#[test]
+fn coalition_leave_needs_the_other_members() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		create_brand(2, b"Tea House");
+		assert_ok!(BrandAdmin::create_coalition(Origin::signed(1), b"Mall".to_vec()));
+
+		assert_noop!(
+			BrandAdmin::propose_leave(Origin::signed(1)),
+			Error::<Test>::LastCoalitionMember
+		);
+		assert_noop!(
+			BrandAdmin::propose_leave(Origin::signed(2)),
+			Error::<Test>::NotCoalitionMember
+		);
+
+		assert_ok!(BrandAdmin::propose_join(Origin::signed(2), 0));
+		assert_ok!(BrandAdmin::approve_membership_change(Origin::signed(1), 0, 2));
+		assert_ok!(BrandAdmin::propose_leave(Origin::signed(2)));
+		System::assert_last_event(
+			BrandAdminEvent::CoalitionChangeProposed {
+				coalition_id: 0,
+				brand_id: 2,
+				change: MembershipChange::Leave,
+			}
+			.into(),
+		);
+		assert_noop!(
+			BrandAdmin::approve_membership_change(Origin::signed(2), 0, 2),
+			Error::<Test>::AlreadyApproved
+		);
+		assert_ok!(BrandAdmin::approve_membership_change(Origin::signed(1), 0, 2));
+
+		assert_eq!(CoalitionOf::<Test>::get(2), None);
+		assert_eq!(Coalitions::<Test>::get(0).unwrap().members.into_inner(), vec![1]);
+		System::assert_last_event(
+			BrandAdminEvent::CoalitionLeft { coalition_id: 0, brand_id: 2 }.into(),
+		);
+	});
+}
+
+#[test]
+fn removing_a_brand_leaves_its_coalition() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		create_brand(2, b"Tea House");
+		assert_ok!(BrandAdmin::create_coalition(Origin::signed(1), b"Mall".to_vec()));
+		assert_ok!(BrandAdmin::propose_join(Origin::signed(2), 0));
+		assert_ok!(BrandAdmin::approve_membership_change(Origin::signed(1), 0, 2));
+
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(2), RemovalMode::Strict, 0));
+
+		assert_eq!(CoalitionOf::<Test>::get(2), None);
+		assert_eq!(Coalitions::<Test>::get(0).unwrap().members.into_inner(), vec![1]);
+	});
+}
+
+#[test]
+fn outlets_can_be_added_updated_and_removed() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		assert_ok!(BrandAdmin::add_operator(Origin::signed(1), 1, 2, BrandRole::Manager));
+
+		assert_ok!(BrandAdmin::add_outlet(
+			Origin::signed(2),
+			1,
+			b"Main St".to_vec(),
+			b"u33db".to_vec(),
+			Some(5),
+		));
+		System::assert_last_event(
+			BrandAdminEvent::OutletAdded { brand_id: 1, outlet_id: 0 }.into(),
+		);
+		assert_ok!(BrandAdmin::ensure_outlet(&1, 0));
+		assert_ok!(BrandAdmin::ensure_brand_role_at_outlet(&1, &5, BrandRole::Cashier, Some(0)));
+		assert_noop!(
+			BrandAdmin::ensure_brand_role_at_outlet(&1, &5, BrandRole::Cashier, None),
+			Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			BrandAdmin::ensure_brand_role_at_outlet(&1, &5, BrandRole::Cashier, Some(1)),
+			Error::<Test>::OutletNotFound
+		);
+
+		assert_ok!(BrandAdmin::update_outlet(
+			Origin::signed(1),
+			1,
+			0,
+			b"Main St".to_vec(),
+			b"u33db".to_vec(),
+			None,
+		));
+		assert_noop!(
+			BrandAdmin::ensure_brand_role_at_outlet(&1, &5, BrandRole::Cashier, Some(0)),
+			Error::<Test>::NotBrandOperator
+		);
+
+		assert_ok!(BrandAdmin::remove_outlet(Origin::signed(1), 1, 0));
+		assert_noop!(BrandAdmin::ensure_outlet(&1, 0), Error::<Test>::OutletNotFound);
+	});
+}
+
+#[test]
+fn outlet_errors() {
+	new_test_ext().execute_with(|| {
+		create_brand(1, b"Coffee House");
+		assert_ok!(BrandAdmin::add_operator(Origin::signed(1), 1, 2, BrandRole::Cashier));
+
+		assert_noop!(
+			BrandAdmin::add_outlet(Origin::signed(2), 1, b"Main".to_vec(), b"u33d".to_vec(), None),
+			Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			BrandAdmin::add_outlet(Origin::signed(1), 1, b"Main".to_vec(), b"u33a".to_vec(), None),
+			Error::<Test>::InvalidGeohash
+		);
+		assert_noop!(
+			BrandAdmin::add_outlet(Origin::signed(1), 1, b"Main".to_vec(), Vec::new(), None),
+			Error::<Test>::InvalidGeohash
+		);
+		assert_noop!(
+			BrandAdmin::add_outlet(
+				Origin::signed(1),
+				1,
+				b"Main".to_vec(),
+				[b'u'; 13].to_vec(),
+				None
+			),
+			Error::<Test>::InvalidGeohash
+		);
+		assert_noop!(
+			BrandAdmin::add_outlet(
+				Origin::signed(1),
+				1,
+				[b'a'; 17].to_vec(),
+				b"u33d".to_vec(),
+				None
+			),
+			Error::<Test>::NameTooLong
+		);
+		assert_noop!(
+			BrandAdmin::update_outlet(
+				Origin::signed(1),
+				1,
+				0,
+				b"Main".to_vec(),
+				b"u33d".to_vec(),
+				None
+			),
+			Error::<Test>::OutletNotFound
+		);
+		assert_noop!(
+			BrandAdmin::remove_outlet(Origin::signed(1), 1, 0),
+			Error::<Test>::OutletNotFound
+		);
+	});
+}
+
+#[test]
+fn migration_v1_builds_name_index() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<BrandAdmin>();
+		BrandById::<Test>::insert(1, crate::Brand { name: bounded_name(b"Coffee  House") });
+		BrandById::<Test>::insert(2, crate::Brand { name: bounded_name(b"  ") });
+
+		crate::migrations::v1::migrate::<Test>();
+
+		assert_eq!(BrandIdByName::<Test>::get(bounded_name(b"coffee house")), Some(1));
+		assert_eq!(BrandIdByName::<Test>::iter().count(), 1);
+		assert_eq!(BrandAdmin::on_chain_storage_version(), 1);
+	});
+}
+
+#[test]
+fn migration_v2_bounds_names_and_coalitions() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(1).put::<BrandAdmin>();
+		let long_name = b"A Very Long Coffee House".to_vec();
+		unhashed::put(&BrandById::<Test>::hashed_key_for(1), &long_name);
+		let mut old_key = BrandIdByName::<Test>::final_prefix().to_vec();
+		old_key.extend(Blake2_128Concat::hash(&BrandAdmin::normalize_name(&long_name).encode()));
+		unhashed::put(&old_key, &1u64);
+
+		unhashed::put(
+			&Coalitions::<Test>::hashed_key_for(0),
+			&(b"Mall".to_vec(), vec![1u64, 2, 3, 4]),
+		);
+		for who in 1..=4 {
+			CoalitionOf::<Test>::insert(who, 0);
+		}
+
+		crate::migrations::v2::migrate::<Test>();
+
+		let brand = BrandById::<Test>::get(1).unwrap();
+		assert_eq!(brand.name.into_inner(), long_name[..16].to_vec());
+		assert_eq!(unhashed::get::<u64>(&old_key), None);
+		assert_eq!(BrandIdByName::<Test>::get(bounded_name(b"a very long coff")), Some(1));
+		assert_eq!(Coalitions::<Test>::get(0).unwrap().members.into_inner(), vec![1, 2, 3]);
+		assert_eq!(CoalitionOf::<Test>::get(4), None);
+		assert_eq!(BrandAdmin::on_chain_storage_version(), 2);
+	});
+}
@@ -2,14 +2,22 @@
 
 pub use pallet::*;
 
+pub mod migrations;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
 #[frame_support::pallet]
 pub mod pallet {
 	use frame_support::{
 		pallet_prelude::*,
 		sp_runtime::traits::{Scale},
-		traits::{tokens::ExistenceRequirement, Currency, Randomness, ReservableCurrency, Time, Len},
+		traits::{tokens::ExistenceRequirement, BalanceStatus, Currency, Randomness, ReservableCurrency, Time},
 		transactional, require_transactional,
-		sp_runtime::traits::Saturating,
+		sp_runtime::traits::{Saturating, Zero},
 	};
 	use frame_system::pallet_prelude::*;
 	use scale_info::{TypeInfo, StaticTypeInfo};
@@ -17,6 +25,7 @@ pub mod pallet {
 	use sp_std::vec::Vec;
 	use sp_runtime::SaturatedConversion;
 	use pallet_brand_token::BrandTransferToken;
+	use pallet_brand_admin::{BrandLifecycle, BrandRole, OutletId};
 
 	#[cfg(feature = "std")]
 	use frame_support::serde::{Deserialize, Serialize};
@@ -25,24 +34,44 @@ pub mod pallet {
 	type BalanceOf<T> =
 		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
-	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
-	#[scale_info(skip_type_params(T))]
-	pub struct NFTCollection<Account, Balance, Time> {
-		pub title: Vec<u8>,
-		pub description: Option<Vec<u8>>,
+	pub type CollectionOf<T> = NFTCollection<
+		<T as frame_system::Config>::AccountId,
+		BalanceOf<T>,
+		<T as Config>::Moment,
+		<T as pallet_brand_admin::Config>::MaxNameLen,
+		<T as Config>::MaxMediaLen,
+	>;
+	pub type NftOf<T> = NonFungibleToken<
+		<T as frame_system::Config>::AccountId,
+		BalanceOf<T>,
+		<T as Config>::Moment,
+		<T as pallet_brand_admin::Config>::MaxNameLen,
+		<T as Config>::MaxMediaLen,
+	>;
+
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(TitleLimit, TextLimit))]
+	#[codec(mel_bound(Account: MaxEncodedLen, Balance: MaxEncodedLen, Time: MaxEncodedLen))]
+	pub struct NFTCollection<Account, Balance, Time, TitleLimit: Get<u32>, TextLimit: Get<u32>> {
+		pub title: BoundedVec<u8, TitleLimit>,
+		pub description: Option<BoundedVec<u8, TextLimit>>,
 		pub creator: Account,
 		pub deposit: Balance,
 		pub expire: u8,
 		pub created_at: Time
 	}
 
-	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
-	#[scale_info(skip_type_params(T))]
-	pub struct NonFungibleToken<Account, Balance, Time> {
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(TitleLimit, TextLimit))]
+	#[codec(mel_bound(Account: MaxEncodedLen, Balance: MaxEncodedLen, Time: MaxEncodedLen))]
+	pub struct NonFungibleToken<Account, Balance, Time, TitleLimit: Get<u32>, TextLimit: Get<u32>> {
 		pub id: [u8; 16],
-		pub title: Vec<u8>,
-		pub description: Option<Vec<u8>>,
-		pub media: Vec<u8>, // URI to associated media, preferably to decentralized, content-addressed storage
+		pub title: BoundedVec<u8, TitleLimit>,
+		pub description: Option<BoundedVec<u8, TextLimit>>,
+		pub media: BoundedVec<u8, TextLimit>, // URI to associated media, preferably to decentralized, content-addressed storage
 		pub creator: Account,
 		pub owner: Account,
 		pub collection_id: [u8; 16],
@@ -56,7 +85,7 @@ pub mod pallet {
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
-	#[pallet::without_storage_info]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	// Configure the pallet by specifying the parameters and types on which it depends.
@@ -86,6 +115,10 @@ pub mod pallet {
 		type Timestamp: Time<Moment = Self::Moment>;
 
 		type BrandCurrency: BrandTransferToken<Self::AccountId>;
+
+		/// Maximum length of an NFT or collection description and of an NFT media URI.
+		#[pallet::constant]
+		type MaxMediaLen: Get<u32>;
 	}
 
 	// Errors
@@ -104,7 +137,14 @@ pub mod pallet {
 		PriceNotMatch,
 		BrandNotExist,
 		NotPayExactAmount,
-		Invalid
+		Invalid,
+		DepositNotMoved,
+		/// The title is longer than `MaxNameLen`.
+		TitleTooLong,
+		/// The description or media URI is longer than `MaxMediaLen`.
+		TextTooLong,
+		/// The NFT was not redeemed, or its outlet has already been recorded.
+		NotAwaitingOutlet,
 	}
 
 	// Events
@@ -124,15 +164,40 @@ pub mod pallet {
 		Paid { nft_id: [u8; 16] },
 		ReturnedOverdueNFT { nft_id: [u8; 16] },
 		RenewNFT { nft: [u8; 16], price: BalanceOf<T> },
+		Redeemed { nft: [u8; 16], who: T::AccountId },
+		RedemptionOutletRecorded { nft: [u8; 16], outlet: OutletId },
 	}
 
 	#[pallet::storage]
 	#[pallet::getter(fn collection_by_id)]
-	pub(super) type CollectionById<T: Config> = StorageMap<_, Twox64Concat, [u8; 16], NFTCollection<T::AccountId, BalanceOf<T>, T::Moment>>;
+	pub(super) type CollectionById<T: Config> = StorageMap<_, Twox64Concat, [u8; 16], CollectionOf<T>>;
 
 	#[pallet::storage]
 	#[pallet::getter(fn token_by_id)]
-	pub(super) type TokenById<T: Config> = StorageMap<_, Twox64Concat, [u8; 16], NonFungibleToken<T::AccountId, BalanceOf<T>, T::Moment>>;
+	pub(super) type TokenById<T: Config> = StorageMap<_, Twox64Concat, [u8; 16], NftOf<T>>;
+
+	/// Collections created by each brand.
+	#[pallet::storage]
+	pub(super) type CollectionsByCreator<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, [u8; 16], ()>;
+
+	/// NFTs minted by each brand, whoever owns them now.
+	#[pallet::storage]
+	pub(super) type NftsByCreator<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, [u8; 16], ()>;
+
+	/// NFTs redeemed by a customer whose outlet the brand has yet to record, see
+	/// `record_redemption_outlet`.
+	#[pallet::storage]
+	pub(super) type AwaitingOutlet<T: Config> = StorageMap<_, Twox64Concat, [u8; 16], ()>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			crate::migrations::v1::migrate::<T>()
+				.saturating_add(crate::migrations::v2::migrate::<T>())
+		}
+	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
@@ -140,6 +205,7 @@ pub mod pallet {
 		#[transactional]
 		pub fn mint_nft(
 			origin: OriginFor<T>,
+			brand_id: T::AccountId,
 			title: Vec<u8>,
 			description: Option<Vec<u8>>,
 			media: Vec<u8>,
@@ -151,23 +217,27 @@ pub mod pallet {
 			// Make sure the caller is from a signed origin
 			let sender = ensure_signed(origin)?;
 
-			let brand = pallet_brand_admin::Pallet::<T>::brand_by_id(&sender);
-			ensure!(brand != None, Error::<T>::BrandNotExist);
+			// Brand operators with the `Minter` role mint on behalf of the brand
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Minter)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_active(&brand_id)?;
 
 			let nft_id = Self::gen_id();
 
-			let data_compressed:u8 = u8::try_from(title.len()).unwrap().saturating_add(u8::try_from(description.len()).unwrap()).saturating_add(u8::try_from(media.len()).unwrap()).saturating_add(u8::try_from(collection_id.len()).unwrap()).saturating_add(32);
-			let data_deposit = T::DataDepositPerByte::get().saturating_mul(data_compressed.into());
-			
-			T::Currency::reserve(&sender, data_deposit.clone())?;
+			let title = Self::bounded(title, Error::<T>::TitleTooLong)?;
+			let description = description.map(|d| Self::bounded(d, Error::<T>::TextTooLong)).transpose()?;
+			let media = Self::bounded(media, Error::<T>::TextTooLong)?;
+
+			let data_deposit = Self::nft_deposit(&title, &description, &media);
+
+			T::Currency::reserve(&brand_id, data_deposit.clone())?;
 			
-			let nft = NonFungibleToken::<T::AccountId, BalanceOf<T>, T::Moment> { 
+			let nft = NftOf::<T> { 
 				id: nft_id.clone(),
 				title,
 				description,
 				media,
-				creator: sender.clone(),
-				owner: sender.clone(),
+				creator: brand_id.clone(),
+				owner: brand_id.clone(),
 				collection_id,
 				deposit: data_deposit,
 				price,
@@ -180,9 +250,10 @@ pub mod pallet {
 			ensure!(!TokenById::<T>::contains_key(&nft_id), Error::<T>::DuplicateNFT);
 
 			TokenById::<T>::insert(nft_id, nft);
+			NftsByCreator::<T>::insert(&brand_id, nft_id, ());
 
 			// Deposit our event.
-			Self::deposit_event(Event::Created { nft: nft_id, owner: sender });
+			Self::deposit_event(Event::Created { nft: nft_id, owner: brand_id });
 
 			Ok(())
 		}
@@ -200,15 +271,18 @@ pub mod pallet {
 
 			let brand = pallet_brand_admin::Pallet::<T>::brand_by_id(&sender);
 			ensure!(brand != None, Error::<T>::BrandNotExist);
+			pallet_brand_admin::Pallet::<T>::ensure_brand_active(&sender)?;
 
 			let collection_id = Self::gen_id();
 
-			let data_compressed:u32 = u32::try_from(title.len()).unwrap().saturating_add(u32::try_from(description.len()).unwrap()).saturating_add(16);
-			let data_deposit = T::DataDepositPerByte::get().saturating_mul(data_compressed.into());
+			let title = Self::bounded(title, Error::<T>::TitleTooLong)?;
+			let description = description.map(|d| Self::bounded(d, Error::<T>::TextTooLong)).transpose()?;
+
+			let data_deposit = Self::collection_deposit(&title, &description);
 
 			T::Currency::reserve(&sender, data_deposit.clone())?;
 			
-			let collection = NFTCollection::<T::AccountId, BalanceOf<T>, T::Moment> { 
+			let collection = CollectionOf::<T> { 
 				title,
 				description,
 				creator: sender.clone(),
@@ -220,6 +294,7 @@ pub mod pallet {
 			ensure!(!CollectionById::<T>::contains_key(&collection_id), Error::<T>::DuplicateCollection);
 
 			CollectionById::<T>::insert(collection_id, collection);
+			CollectionsByCreator::<T>::insert(&sender, collection_id, ());
 
 			// Deposit our event.
 			Self::deposit_event(Event::CreatedCollection { collection: collection_id, owner: sender });
@@ -256,6 +331,7 @@ pub mod pallet {
 			T::Currency::unreserve(&sender, collection.deposit);
 
 			CollectionById::<T>::remove(collection_id.clone());
+			CollectionsByCreator::<T>::remove(&sender, collection_id);
 
 			// Deposit our event.
 			Self::deposit_event(Event::DestroyCollection { collection: collection_id });
@@ -282,17 +358,17 @@ pub mod pallet {
 			let mut nft = TokenById::<T>::get(&nft_id).ok_or(Error::<T>::NoNFT)?;
 			ensure!(nft.owner == sender.clone(), Error::<T>::NotOwner);
 
+			nft.title = Self::bounded(title, Error::<T>::TitleTooLong)?;
+			nft.description = description.map(|d| Self::bounded(d, Error::<T>::TextTooLong)).transpose()?;
+			nft.media = Self::bounded(media, Error::<T>::TextTooLong)?;
+			nft.collection_id = collection_id;
+
 			T::Currency::unreserve(&sender, nft.deposit);
 
-			let data_compressed:u32 = u32::try_from(title.len()).unwrap().saturating_add(u32::try_from(description.len()).unwrap()).saturating_add(u32::try_from(media.len()).unwrap()).saturating_add(u32::try_from(collection_id.len()).unwrap()).saturating_add(32);
-			let data_deposit = T::DataDepositPerByte::get().saturating_mul(data_compressed.into());
+			let data_deposit = Self::nft_deposit(&nft.title, &nft.description, &nft.media);
 
 			T::Currency::reserve(&sender, data_deposit.clone())?;
 
-			nft.title = title;
-			nft.description = description;
-			nft.media = media;
-			nft.collection_id = collection_id;
 			nft.deposit = data_deposit;
 
 			TokenById::<T>::insert(nft_id, nft);
@@ -320,15 +396,16 @@ pub mod pallet {
 			let mut collection = CollectionById::<T>::get(&collection_id).ok_or(Error::<T>::NoCollection)?;
 			ensure!(collection.creator == sender.clone(), Error::<T>::NotOwner);
 
+			if let Some(title) = title {
+				collection.title = Self::bounded(title, Error::<T>::TitleTooLong)?;
+			}
+			collection.description = description.map(|d| Self::bounded(d, Error::<T>::TextTooLong)).transpose()?;
+
 			T::Currency::unreserve(&sender, collection.deposit);
 
-			let data_compressed:u32 = u32::try_from(title.len()).unwrap().saturating_add(u32::try_from(description.len()).unwrap()).saturating_add(16);
-			let data_deposit = T::DataDepositPerByte::get().saturating_mul(data_compressed.into());
+			let data_deposit = Self::collection_deposit(&collection.title, &collection.description);
 
 			T::Currency::reserve(&sender, data_deposit.clone())?;
-			
-			collection.title = title.unwrap();
-			collection.description = description;
 			collection.deposit = data_deposit;
 			
 			CollectionById::<T>::insert(collection_id, collection);
@@ -353,7 +430,9 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		/// Redeem an NFT the brand sells for points. The customer cannot vouch for where they
+		/// redeemed it, so the outlet is left for the brand to record.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2))]
 		#[transactional]
 		pub fn redeem_nft(
 			origin: OriginFor<T>,
@@ -363,16 +442,19 @@ pub mod pallet {
 
 			let mut nft = TokenById::<T>::get(&nft_id).ok_or(Error::<T>::NoNFT)?;
 			let from = nft.owner.clone();
+			pallet_brand_admin::Pallet::<T>::ensure_not_suspended(&nft.creator)?;
 
 			ensure!(nft.price != Self::u8_to_balance(0u8), Error::<T>::NotSelling);
 			ensure!(from != sender.clone(), Error::<T>::TransferToSelf);
 			ensure!(nft.creator == nft.owner, Error::<T>::Invalid);
 
+			let points: <T::BrandCurrency as BrandTransferToken<T::AccountId>>::Balance =
+				nft.price.saturated_into::<u128>().try_into().map_err(|_| Error::<T>::Invalid)?;
 			T::BrandCurrency::do_transfer(
 				sender.clone(),
 				nft.owner.clone(),
 				nft.owner.clone(),
-				Self::balance_to_u32(nft.price)
+				points
 			)?;
 
 			let old_owner = from;
@@ -390,11 +472,39 @@ pub mod pallet {
 			nft.owner = new_owner.clone();
 			nft.price = default_price.clone();
 			
+			T::Currency::unreserve(&old_owner, nft.deposit);
+
 			nft.deposit = default_price.clone();
 			// Write updates to storage
 			TokenById::<T>::insert(&nft_id, nft);
+			AwaitingOutlet::<T>::insert(&nft_id, ());
 
 			Self::deposit_event(Event::Transferred { from: old_owner, to: new_owner.clone(), nft: nft_id });
+			Self::deposit_event(Event::Redeemed { nft: nft_id, who: new_owner });
+
+			Ok(())
+		}
+
+		/// Record the outlet a redeemed NFT was redeemed at. Only the brand, one of its
+		/// `Cashier` operators, or the POS operator of `outlet` may do so, once per redemption.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 1))]
+		pub fn record_redemption_outlet(
+			origin: OriginFor<T>,
+			nft_id: [u8; 16],
+			outlet: OutletId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let nft = TokenById::<T>::get(&nft_id).ok_or(Error::<T>::NoNFT)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role_at_outlet(
+				&nft.creator,
+				&sender,
+				BrandRole::Cashier,
+				Some(outlet),
+			)?;
+			AwaitingOutlet::<T>::take(&nft_id).ok_or(Error::<T>::NotAwaitingOutlet)?;
+
+			Self::deposit_event(Event::RedemptionOutletRecorded { nft: nft_id, outlet });
 
 			Ok(())
 		}
@@ -418,6 +528,8 @@ pub mod pallet {
 
 			// Set the price in storage
 			TokenById::<T>::remove(&nft_id);
+			NftsByCreator::<T>::remove(&nft.creator, nft_id);
+			AwaitingOutlet::<T>::remove(&nft_id);
 
 			Self::deposit_event(Event::BurntNFT { nft: nft_id });
 
@@ -502,6 +614,7 @@ pub mod pallet {
 		) -> DispatchResult {
 			let mut nft = TokenById::<T>::get(&nft_id).ok_or(Error::<T>::NoNFT)?;
 			let from = nft.owner;
+			pallet_brand_admin::Pallet::<T>::ensure_not_suspended(&nft.creator)?;
 
 			ensure!(nft.price != Self::u8_to_balance(0u8), Error::<T>::NotSelling);
 			ensure!(from != to.clone(), Error::<T>::TransferToSelf);
@@ -523,7 +636,7 @@ pub mod pallet {
 			nft.owner = new_owner.clone();
 			nft.price = default_price.clone();
 			
-			T::Currency::unreserve(&old_owner, nft.deposit.saturating_add(nft.deposit));
+			T::Currency::unreserve(&old_owner, nft.deposit);
 			
 			nft.deposit = default_price.clone();
 			// Write updates to storage
@@ -550,10 +663,47 @@ pub mod pallet {
 			TryInto::<u32>::try_into(input).ok().unwrap()
 		}
 
-		pub fn get_nfts() -> Vec<NonFungibleToken<T::AccountId, BalanceOf<T>, T::Moment>> {
+		pub fn get_nfts() -> Vec<NftOf<T>> {
 			TokenById::<T>::iter_values().collect()
 		}
 
+		fn bounded<S: Get<u32>>(value: Vec<u8>, error: Error<T>) -> Result<BoundedVec<u8, S>, Error<T>> {
+			value.try_into().map_err(|_| error)
+		}
+
+		/// Deposit for an NFT with the given data, plus its 16 byte collection id and 32 bytes of
+		/// fixed fields.
+		fn nft_deposit<S: Get<u32>, M: Get<u32>>(
+			title: &BoundedVec<u8, S>,
+			description: &Option<BoundedVec<u8, M>>,
+			media: &BoundedVec<u8, M>,
+		) -> BalanceOf<T> {
+			let bytes = Self::data_len(title, description)
+				.saturating_add(media.len() as u32)
+				.saturating_add(16)
+				.saturating_add(32);
+			T::DataDepositPerByte::get().saturating_mul(bytes.into())
+		}
+
+		/// Deposit for a collection with the given data, plus 16 bytes of fixed fields.
+		fn collection_deposit<S: Get<u32>, M: Get<u32>>(
+			title: &BoundedVec<u8, S>,
+			description: &Option<BoundedVec<u8, M>>,
+		) -> BalanceOf<T> {
+			let bytes = Self::data_len(title, description).saturating_add(16);
+			T::DataDepositPerByte::get().saturating_mul(bytes.into())
+		}
+
+		/// Bytes of a title and an optional description. Both are bounded by a `u32`, so the
+		/// lengths fit.
+		fn data_len<S: Get<u32>, M: Get<u32>>(
+			title: &BoundedVec<u8, S>,
+			description: &Option<BoundedVec<u8, M>>,
+		) -> u32 {
+			(title.len() as u32)
+				.saturating_add(description.as_ref().map_or(0, |d| d.len() as u32))
+		}
+
 		pub fn check_expire_nft() -> DispatchResult {
 			let now = T::Timestamp::now();
 			let nfts = Self::get_nfts();
@@ -563,6 +713,10 @@ pub mod pallet {
 				let order_time_u64 = nft.created_at.saturated_into::<u64>();
 				let diff = now_u64.saturating_sub(order_time_u64);
 				let expire_months = nft.expire;
+				// nfts of removed brands have no one to go back to
+				if pallet_brand_admin::Pallet::<T>::brand_by_id(&nft.creator).is_none() {
+					continue;
+				}
 				// check if over 30*expire_months days + 7days
 				if diff > 2592000.saturating_mul(expire_months.into()).saturating_add(604800) && nft.owner != nft.creator {
 					// return nft to brand
@@ -576,4 +730,94 @@ pub mod pallet {
 			Ok(())
 		}
 	}
-}
\ No newline at end of file
+
+	impl<T: Config> BrandLifecycle<T::AccountId> for Pallet<T> {
+		/// NFTs the brand account bought from others are not brand assets and stay with `from`.
+		fn on_brand_transferred(
+			from: &T::AccountId,
+			to: &T::AccountId,
+			budget: &mut u32,
+		) -> DispatchResult {
+			let collections = pallet_brand_admin::Pallet::<T>::take_items(
+				CollectionsByCreator::<T>::drain_prefix(from),
+				budget,
+			)?;
+			for (collection_id, ()) in collections {
+				if let Some(mut collection) = CollectionById::<T>::get(collection_id) {
+					Self::move_reserve(from, to, collection.deposit)?;
+					collection.creator = to.clone();
+					CollectionById::<T>::insert(collection_id, collection);
+				}
+				CollectionsByCreator::<T>::insert(to, collection_id, ());
+			}
+
+			let nfts = pallet_brand_admin::Pallet::<T>::take_items(
+				NftsByCreator::<T>::drain_prefix(from),
+				budget,
+			)?;
+			for (nft_id, ()) in nfts {
+				if let Some(mut nft) = TokenById::<T>::get(nft_id) {
+					// a non-zero deposit is only held while the brand still owns the nft it minted
+					Self::move_reserve(from, to, nft.deposit)?;
+					nft.creator = to.clone();
+					if &nft.owner == from {
+						nft.owner = to.clone();
+					}
+					TokenById::<T>::insert(nft_id, nft);
+				}
+				NftsByCreator::<T>::insert(to, nft_id, ());
+			}
+
+			Ok(())
+		}
+
+		/// Only NFTs the brand still owns count, those bought or redeemed are the customers'.
+		fn has_live_assets(brand: &T::AccountId) -> bool {
+			NftsByCreator::<T>::iter_prefix(brand).any(|(nft_id, ())| {
+				TokenById::<T>::get(nft_id).map_or(false, |nft| &nft.owner == brand)
+			}) || CollectionsByCreator::<T>::iter_prefix(brand).next().is_some()
+		}
+
+		/// Burns the NFTs the brand still owns. NFTs customers bought or redeemed are theirs and
+		/// stay with them.
+		fn on_brand_removed(brand: &T::AccountId, budget: &mut u32) -> DispatchResult {
+			let nfts = pallet_brand_admin::Pallet::<T>::take_items(
+				NftsByCreator::<T>::drain_prefix(brand),
+				budget,
+			)?;
+			for (nft_id, ()) in nfts {
+				// there is no one left to record where it was redeemed
+				AwaitingOutlet::<T>::remove(&nft_id);
+				match TokenById::<T>::get(&nft_id) {
+					Some(nft) if &nft.owner == brand => {
+						T::Currency::unreserve(brand, nft.deposit);
+						TokenById::<T>::remove(&nft_id);
+						Self::deposit_event(Event::BurntNFT { nft: nft_id });
+					},
+					_ => {},
+				}
+			}
+
+			let collections = pallet_brand_admin::Pallet::<T>::take_items(
+				CollectionsByCreator::<T>::drain_prefix(brand),
+				budget,
+			)?;
+			for (collection_id, ()) in collections {
+				if let Some(collection) = CollectionById::<T>::take(&collection_id) {
+					T::Currency::unreserve(brand, collection.deposit);
+					Self::deposit_event(Event::DestroyCollection { collection: collection_id });
+				}
+			}
+
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		fn move_reserve(from: &T::AccountId, to: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			let remaining = T::Currency::repatriate_reserved(from, to, amount, BalanceStatus::Reserved)?;
+			ensure!(remaining.is_zero(), Error::<T>::DepositNotMoved);
+			Ok(())
+		}
+	}
+}
@@ -0,0 +1,122 @@
+//! Storage migrations for the brand membership pallet.
+
+pub mod v1 {
+	use crate::{CollectionById, CollectionOf, Config, NftOf, Pallet, TokenById};
+	use codec::Decode;
+	use frame_support::{
+		traits::{Get, GetStorageVersion, StorageVersion},
+		weights::Weight,
+		BoundedVec,
+	};
+	use sp_std::vec::Vec;
+
+	#[derive(Decode)]
+	struct OldNFTCollection<Account, Balance, Time> {
+		title: Vec<u8>,
+		description: Option<Vec<u8>>,
+		creator: Account,
+		deposit: Balance,
+		expire: u8,
+		created_at: Time,
+	}
+
+	#[derive(Decode)]
+	struct OldNonFungibleToken<Account, Balance, Time> {
+		id: [u8; 16],
+		title: Vec<u8>,
+		description: Option<Vec<u8>>,
+		media: Vec<u8>,
+		creator: Account,
+		owner: Account,
+		collection_id: [u8; 16],
+		deposit: Balance,
+		price: Balance,
+		expire: u8,
+		created_at: Time,
+		renew_time: Time,
+		renew_fee: Balance,
+	}
+
+	fn truncate<S: Get<u32>>(mut value: Vec<u8>) -> BoundedVec<u8, S> {
+		value.truncate(S::get() as usize);
+		value.try_into().unwrap_or_default()
+	}
+
+	/// Move collections and NFTs to bounded storage, truncating titles longer than `MaxNameLen`
+	/// and descriptions and media URIs longer than `MaxMediaLen`. Deposits are left as they are.
+	pub fn migrate<T: Config>() -> Weight {
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+		if on_chain_version >= 1 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut translated: Weight = 0;
+		CollectionById::<T>::translate::<OldNFTCollection<T::AccountId, _, T::Moment>, _>(
+			|_, old| {
+				translated += 1;
+				Some(CollectionOf::<T> {
+					title: truncate(old.title),
+					description: old.description.map(truncate),
+					creator: old.creator,
+					deposit: old.deposit,
+					expire: old.expire,
+					created_at: old.created_at,
+				})
+			},
+		);
+
+		TokenById::<T>::translate::<OldNonFungibleToken<T::AccountId, _, T::Moment>, _>(
+			|_, old| {
+				translated += 1;
+				Some(NftOf::<T> {
+					id: old.id,
+					title: truncate(old.title),
+					description: old.description.map(truncate),
+					media: truncate(old.media),
+					creator: old.creator,
+					owner: old.owner,
+					collection_id: old.collection_id,
+					deposit: old.deposit,
+					price: old.price,
+					expire: old.expire,
+					created_at: old.created_at,
+					renew_time: old.renew_time,
+					renew_fee: old.renew_fee,
+				})
+			},
+		);
+
+		StorageVersion::new(1).put::<Pallet<T>>();
+		T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+	}
+}
+
+pub mod v2 {
+	use crate::{CollectionById, CollectionsByCreator, Config, NftsByCreator, Pallet, TokenById};
+	use frame_support::{
+		traits::{Get, GetStorageVersion, StorageVersion},
+		weights::Weight,
+	};
+
+	/// Build the `CollectionsByCreator` and `NftsByCreator` indices from the existing
+	/// collections and NFTs.
+	pub fn migrate<T: Config>() -> Weight {
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+		if on_chain_version != 1 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut indexed: Weight = 0;
+		for (collection_id, collection) in CollectionById::<T>::iter() {
+			CollectionsByCreator::<T>::insert(collection.creator, collection_id, ());
+			indexed += 1;
+		}
+		for (nft_id, nft) in TokenById::<T>::iter() {
+			NftsByCreator::<T>::insert(nft.creator, nft_id, ());
+			indexed += 1;
+		}
+
+		StorageVersion::new(2).put::<Pallet<T>>();
+		T::DbWeight::get().reads_writes(indexed + 1, indexed + 1)
+	}
+}
@@ -0,0 +1,147 @@
+use crate as pallet_brand_membership;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64},
+	PalletId,
+};
+use frame_system as system;
+use pallet_brand_token::BrandTransferToken;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	DispatchResult,
+};
+use std::cell::RefCell;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub const BRAND_DEPOSIT: u64 = 100;
+pub const WIND_DOWN_PERIOD: u64 = 10;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
+		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
+		BrandAdmin: pallet_brand_admin::{Pallet, Call, Storage, Event<T>},
+		BrandMembership: pallet_brand_membership::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<5>;
+	type WeightInfo = ();
+}
+
+impl pallet_randomness_collective_flip::Config for Test {}
+
+parameter_types! {
+	pub const CoalitionPalletId: PalletId = PalletId(*b"loy/coal");
+}
+
+impl pallet_brand_admin::Config for Test {
+	type Event = Event;
+	type DepositCurrency = Balances;
+	type ProfileDepositPerByte = ConstU64<1>;
+	type MaxNameLen = ConstU32<16>;
+	type MaxProfileFieldLen = ConstU32<32>;
+	type BrandLifecycle = BrandMembership;
+	type WindDownPeriod = ConstU64<WIND_DOWN_PERIOD>;
+	type VerifierOrigin = frame_system::EnsureRoot<u64>;
+	type BrandDeposit = ConstU64<BRAND_DEPOSIT>;
+	type Slashed = ();
+	type CoalitionPalletId = CoalitionPalletId;
+	type MaxCoalitionMembers = ConstU32<3>;
+}
+
+thread_local! {
+	static POINT_TRANSFERS: RefCell<Vec<(u64, u64, u64, u64)>> = RefCell::new(Vec::new());
+}
+
+/// The `(from, to, brand, amount)` point transfers made so far.
+pub fn point_transfers() -> Vec<(u64, u64, u64, u64)> {
+	POINT_TRANSFERS.with(|v| v.borrow().clone())
+}
+
+/// Stands in for the brand token pallet, recording the points NFTs are redeemed for.
+pub struct MockPoints;
+
+impl BrandTransferToken<u64> for MockPoints {
+	type Balance = u64;
+
+	fn do_transfer(from: u64, to: u64, brand_id: u64, amount: u64) -> DispatchResult {
+		POINT_TRANSFERS.with(|v| v.borrow_mut().push((from, to, brand_id, amount)));
+		Ok(())
+	}
+}
+
+impl pallet_brand_membership::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type NFTRandomness = RandomnessCollectiveFlip;
+	type DataDepositPerByte = ConstU64<1>;
+	type Moment = u64;
+	type Timestamp = Timestamp;
+	type BrandCurrency = MockPoints;
+	type MaxMediaLen = ConstU32<32>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: (1..=6).map(|who| (who, 1_000)).collect() }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,410 @@
+use crate::{
+	mock::*, AwaitingOutlet, CollectionById, CollectionsByCreator, Error,
+	Event as BrandMembershipEvent, NftsByCreator, TokenById,
+};
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::unhashed,
+	traits::{GetStorageVersion, StorageVersion},
+};
+use pallet_brand_admin::{BrandRole, BrandStatus, RemovalMode};
+use sp_runtime::DispatchResult;
+
+/// Deposit of an NFT minted by `mint`: its title and media plus 48 bytes of fixed fields.
+const NFT_DEPOSIT: u64 = 3 + 10 + 16 + 32;
+
+/// Deposit of a collection created by `create_collection`: its title plus 16 fixed bytes.
+const COLLECTION_DEPOSIT: u64 = 4 + 16;
+
+fn create_brand(who: u64) {
+	assert_ok!(BrandAdmin::create_brand(Origin::signed(who), vec![b'A' + who as u8]));
+}
+
+fn add_operator(brand: u64, operator: u64, role: BrandRole) {
+	assert_ok!(BrandAdmin::add_operator(Origin::signed(brand), brand, operator, role));
+}
+
+fn mint_nft(who: u64, brand: u64, price: u64) -> DispatchResult {
+	BrandMembership::mint_nft(
+		Origin::signed(who),
+		brand,
+		b"Mug".to_vec(),
+		None,
+		b"ipfs://mug".to_vec(),
+		[0; 16],
+		price,
+		1,
+		0,
+	)
+}
+
+/// Mint an NFT for `brand` in a block of its own, so that it gets a fresh id.
+fn mint(who: u64, brand: u64, price: u64) -> [u8; 16] {
+	System::set_block_number(System::block_number() + 1);
+	let nft = BrandMembership::gen_id();
+	assert_ok!(mint_nft(who, brand, price));
+	nft
+}
+
+/// Create a collection for `brand` in a block of its own, so that it gets a fresh id.
+fn create_collection(brand: u64) -> [u8; 16] {
+	System::set_block_number(System::block_number() + 1);
+	let collection = BrandMembership::gen_id();
+	assert_ok!(BrandMembership::create_collection(
+		Origin::signed(brand),
+		b"Cups".to_vec(),
+		None,
+		1
+	));
+	collection
+}
+
+fn owner(nft: [u8; 16]) -> u64 {
+	TokenById::<Test>::get(nft).unwrap().owner
+}
+
+#[test]
+fn minters_mint_nfts_for_the_brand() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		add_operator(1, 2, BrandRole::Minter);
+
+		let nft = mint(2, 1, 10);
+
+		let token = TokenById::<Test>::get(nft).unwrap();
+		assert_eq!((token.creator, token.owner), (1, 1));
+		assert_eq!(token.deposit, NFT_DEPOSIT);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		System::assert_last_event(BrandMembershipEvent::Created { nft, owner: 1 }.into());
+	});
+}
+
+#[test]
+fn mint_nft_needs_the_minter_role() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		add_operator(1, 2, BrandRole::Cashier);
+
+		assert_noop!(mint_nft(2, 1, 10), pallet_brand_admin::Error::<Test>::NotBrandOperator);
+		assert_noop!(mint_nft(3, 1, 10), pallet_brand_admin::Error::<Test>::NotBrandOperator);
+		assert_noop!(mint_nft(3, 3, 10), pallet_brand_admin::Error::<Test>::BrandNotFound);
+	});
+}
+
+#[test]
+fn brand_transfer_moves_nfts_collections_and_their_deposits() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		let collection = create_collection(1);
+		let kept = mint(1, 1, 10);
+		let sold = mint(1, 1, 10);
+		assert_ok!(BrandMembership::buy_nft(Origin::signed(3), sold));
+
+		assert_ok!(BrandAdmin::propose_brand_transfer(Origin::signed(1), 2));
+		assert_ok!(BrandAdmin::accept_brand_transfer(Origin::signed(2), 1, 10));
+
+		assert_eq!(CollectionById::<Test>::get(collection).unwrap().creator, 2);
+		assert!(CollectionsByCreator::<Test>::contains_key(2, collection));
+		let token = TokenById::<Test>::get(kept).unwrap();
+		assert_eq!((token.creator, token.owner), (2, 2));
+		// the customer keeps what they bought, only the brand behind it changes
+		let token = TokenById::<Test>::get(sold).unwrap();
+		assert_eq!((token.creator, token.owner), (2, 3));
+		assert_eq!(NftsByCreator::<Test>::iter_prefix(1).count(), 0);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), BRAND_DEPOSIT + COLLECTION_DEPOSIT + NFT_DEPOSIT);
+	});
+}
+
+#[test]
+fn brand_transfer_counts_nfts_and_collections_against_max_items() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		create_collection(1);
+		mint(1, 1, 10);
+		mint(1, 1, 10);
+		assert_ok!(BrandAdmin::propose_brand_transfer(Origin::signed(1), 2));
+
+		assert_noop!(
+			BrandAdmin::accept_brand_transfer(Origin::signed(2), 1, 2),
+			pallet_brand_admin::Error::<Test>::TooManyBrandItems
+		);
+		assert_ok!(BrandAdmin::accept_brand_transfer(Origin::signed(2), 1, 3));
+	});
+}
+
+#[test]
+fn migration_v2_indexes_nfts_and_collections_by_creator() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		let collection = create_collection(1);
+		let nft = mint(1, 1, 10);
+		CollectionsByCreator::<Test>::remove(1, collection);
+		NftsByCreator::<Test>::remove(1, nft);
+		StorageVersion::new(1).put::<BrandMembership>();
+
+		crate::migrations::v2::migrate::<Test>();
+
+		assert!(CollectionsByCreator::<Test>::contains_key(1, collection));
+		assert!(NftsByCreator::<Test>::contains_key(1, nft));
+		assert_eq!(BrandMembership::on_chain_storage_version(), 2);
+	});
+}
+
+#[test]
+fn strict_removal_waits_for_brand_nfts_and_collections() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		let collection = create_collection(1);
+		assert_noop!(
+			BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::Strict, 10),
+			pallet_brand_admin::Error::<Test>::BrandHasLiveAssets
+		);
+		assert_ok!(BrandMembership::destroy_collection(Origin::signed(1), collection));
+
+		let nft = mint(1, 1, 10);
+		assert_noop!(
+			BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::Strict, 10),
+			pallet_brand_admin::Error::<Test>::BrandHasLiveAssets
+		);
+		assert_ok!(BrandMembership::burn_nft(Origin::signed(1), nft));
+
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::Strict, 10));
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn strict_removal_leaves_nfts_customers_own() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		let sold = mint(1, 1, 10);
+		let redeemed = mint(1, 1, 10);
+		assert_ok!(BrandMembership::buy_nft(Origin::signed(3), sold));
+		assert_ok!(BrandMembership::redeem_nft(Origin::signed(4), redeemed));
+
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::Strict, 10));
+
+		assert_eq!(owner(sold), 3);
+		assert_eq!(owner(redeemed), 4);
+		assert!(!AwaitingOutlet::<Test>::contains_key(redeemed));
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn finalized_removal_burns_brand_nfts_and_keeps_customer_ones() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		let collection = create_collection(1);
+		let kept = mint(1, 1, 10);
+		let sold = mint(1, 1, 10);
+		assert_ok!(BrandMembership::buy_nft(Origin::signed(3), sold));
+
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::WindDown, 10));
+		assert_noop!(mint_nft(1, 1, 10), pallet_brand_admin::Error::<Test>::BrandWindingDown);
+
+		System::set_block_number(System::block_number() + WIND_DOWN_PERIOD);
+		assert_ok!(BrandAdmin::finalize_brand_removal(Origin::signed(4), 1, 10));
+
+		assert!(!TokenById::<Test>::contains_key(kept));
+		assert!(!CollectionById::<Test>::contains_key(collection));
+		assert_eq!(owner(sold), 3);
+		assert_eq!(NftsByCreator::<Test>::iter_prefix(1).count(), 0);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		System::assert_has_event(BrandMembershipEvent::BurntNFT { nft: kept }.into());
+	});
+}
+
+#[test]
+fn suspended_brands_nfts_cannot_be_bought_or_redeemed() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		let nft = mint(1, 1, 10);
+		assert_ok!(BrandAdmin::set_brand_status(Origin::root(), 1, BrandStatus::Suspended));
+
+		assert_noop!(
+			BrandMembership::buy_nft(Origin::signed(3), nft),
+			pallet_brand_admin::Error::<Test>::BrandSuspended
+		);
+		assert_noop!(
+			BrandMembership::redeem_nft(Origin::signed(3), nft),
+			pallet_brand_admin::Error::<Test>::BrandSuspended
+		);
+	});
+}
+
+#[test]
+fn redemption_outlets_are_recorded_once_by_brand_staff() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		add_operator(1, 2, BrandRole::Cashier);
+		assert_ok!(BrandAdmin::add_outlet(
+			Origin::signed(1),
+			1,
+			b"Main".to_vec(),
+			b"u33d".to_vec(),
+			Some(4)
+		));
+		let first = mint(1, 1, 10);
+		let second = mint(1, 1, 10);
+
+		assert_ok!(BrandMembership::redeem_nft(Origin::signed(3), first));
+		System::assert_has_event(BrandMembershipEvent::Redeemed { nft: first, who: 3 }.into());
+		assert_eq!(owner(first), 3);
+		assert_eq!(point_transfers(), vec![(3, 1, 1, 10)]);
+		assert!(AwaitingOutlet::<Test>::contains_key(first));
+
+		assert_noop!(
+			BrandMembership::record_redemption_outlet(Origin::signed(3), first, 0),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_ok!(BrandMembership::record_redemption_outlet(Origin::signed(4), first, 0));
+		System::assert_last_event(
+			BrandMembershipEvent::RedemptionOutletRecorded { nft: first, outlet: 0 }.into(),
+		);
+		assert_noop!(
+			BrandMembership::record_redemption_outlet(Origin::signed(1), first, 0),
+			Error::<Test>::NotAwaitingOutlet
+		);
+
+		assert_ok!(BrandMembership::redeem_nft(Origin::signed(5), second));
+		assert_ok!(BrandMembership::record_redemption_outlet(Origin::signed(2), second, 0));
+		assert!(!AwaitingOutlet::<Test>::contains_key(second));
+	});
+}
+
+#[test]
+fn redemption_errors() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		let free = mint(1, 1, 0);
+		let nft = mint(1, 1, 10);
+
+		assert_noop!(BrandMembership::redeem_nft(Origin::signed(3), [9; 16]), Error::<Test>::NoNFT);
+		assert_noop!(
+			BrandMembership::redeem_nft(Origin::signed(3), free),
+			Error::<Test>::NotSelling
+		);
+		assert_noop!(
+			BrandMembership::redeem_nft(Origin::signed(1), nft),
+			Error::<Test>::TransferToSelf
+		);
+		assert_noop!(
+			BrandMembership::record_redemption_outlet(Origin::signed(1), nft, 0),
+			pallet_brand_admin::Error::<Test>::OutletNotFound
+		);
+
+		assert_ok!(BrandMembership::redeem_nft(Origin::signed(3), nft));
+		assert_noop!(
+			BrandMembership::record_redemption_outlet(Origin::signed(1), [9; 16], 0),
+			Error::<Test>::NoNFT
+		);
+	});
+}
+
+#[test]
+fn nft_and_collection_fields_are_bounded() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		let mint_with = |title: usize, description: usize, media: usize| {
+			BrandMembership::mint_nft(
+				Origin::signed(1),
+				1,
+				vec![b'a'; title],
+				Some(vec![b'a'; description]),
+				vec![b'a'; media],
+				[0; 16],
+				10,
+				1,
+				0,
+			)
+		};
+
+		assert_noop!(mint_with(17, 0, 0), Error::<Test>::TitleTooLong);
+		assert_noop!(mint_with(0, 33, 0), Error::<Test>::TextTooLong);
+		assert_noop!(mint_with(0, 0, 33), Error::<Test>::TextTooLong);
+		assert_noop!(
+			BrandMembership::create_collection(Origin::signed(1), vec![b'a'; 17], None, 1),
+			Error::<Test>::TitleTooLong
+		);
+		assert_noop!(
+			BrandMembership::create_collection(
+				Origin::signed(1),
+				b"Cups".to_vec(),
+				Some(vec![b'a'; 33]),
+				1
+			),
+			Error::<Test>::TextTooLong
+		);
+		assert_ok!(mint_with(16, 32, 32));
+	});
+}
+
+#[test]
+fn edits_resize_the_data_deposit() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		let collection = create_collection(1);
+		let nft = mint(1, 1, 10);
+
+		assert_ok!(BrandMembership::edit_nft(
+			Origin::signed(1),
+			nft,
+			b"Big Mug".to_vec(),
+			Some(b"500ml".to_vec()),
+			b"ipfs://mug".to_vec(),
+			collection
+		));
+		assert_eq!(TokenById::<Test>::get(nft).unwrap().deposit, NFT_DEPOSIT + 4 + 5);
+
+		assert_ok!(BrandMembership::edit_collection(Origin::signed(1), collection, None, None));
+		assert_eq!(CollectionById::<Test>::get(collection).unwrap().deposit, COLLECTION_DEPOSIT);
+		assert_eq!(
+			Balances::reserved_balance(1),
+			BRAND_DEPOSIT + COLLECTION_DEPOSIT + NFT_DEPOSIT + 4 + 5
+		);
+	});
+}
+
+#[test]
+fn migration_v1_truncates_long_fields() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<BrandMembership>();
+		let long = vec![b'a'; 40];
+		unhashed::put(
+			&CollectionById::<Test>::hashed_key_for([1; 16]),
+			&(long.clone(), Some(long.clone()), 1u64, 20u64, 1u8, 0u64),
+		);
+		unhashed::put(
+			&TokenById::<Test>::hashed_key_for([2; 16]),
+			&(
+				[2u8; 16],
+				long.clone(),
+				None::<Vec<u8>>,
+				long.clone(),
+				1u64,
+				3u64,
+				[1u8; 16],
+				0u64,
+				10u64,
+				1u8,
+				0u64,
+				0u64,
+				5u64,
+			),
+		);
+
+		crate::migrations::v1::migrate::<Test>();
+
+		let collection = CollectionById::<Test>::get([1; 16]).unwrap();
+		assert_eq!(collection.title.into_inner(), long[..16].to_vec());
+		assert_eq!(collection.description.unwrap().into_inner(), long[..32].to_vec());
+		assert_eq!(collection.deposit, 20);
+		let nft = TokenById::<Test>::get([2; 16]).unwrap();
+		assert_eq!(nft.title.into_inner(), long[..16].to_vec());
+		assert_eq!(nft.media.into_inner(), long[..32].to_vec());
+		assert_eq!((nft.creator, nft.owner, nft.renew_fee), (1, 3, 5));
+		assert_eq!(BrandMembership::on_chain_storage_version(), 1);
+	});
+}
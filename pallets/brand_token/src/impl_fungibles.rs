@@ -0,0 +1,126 @@
+//! Implementations of the `fungibles` traits for brand points.
+//!
+//! Each brand token is an asset, identified by the `BrandAssetId` it got on creation. The brand
+//! account holds the pool; any other account holds the live points of its grants, spent oldest
+//! first.
+//!
+//! Assets are not keyed by the brand account itself because `fungibles::Inspect::AssetId` must be
+//! `Copy`, which `AccountId` is not. `AssetIds` and `AssetBrands` map between the two.
+
+use super::*;
+use frame_support::{
+	dispatch::{DispatchError, DispatchResult},
+	sp_runtime::traits::{CheckedAdd, Zero},
+	traits::{
+		tokens::{fungibles, DepositConsequence, WithdrawConsequence},
+		Get,
+	},
+};
+
+impl<T: Config> fungibles::Inspect<T::AccountId> for Pallet<T> {
+	type AssetId = BrandAssetId;
+	type Balance = T::PointBalance;
+
+	fn total_issuance(asset: BrandAssetId) -> T::PointBalance {
+		AssetBrands::<T>::get(asset)
+			.and_then(|brand_id| BrandTokenById::<T>::get(brand_id))
+			.map_or_else(Zero::zero, |token| token.staked)
+	}
+
+	fn minimum_balance(_asset: BrandAssetId) -> T::PointBalance {
+		Zero::zero()
+	}
+
+	fn balance(asset: BrandAssetId, who: &T::AccountId) -> T::PointBalance {
+		match AssetBrands::<T>::get(asset) {
+			Some(brand_id) if brand_id == *who =>
+				BrandTokenById::<T>::get(&brand_id).map_or_else(Zero::zero, |token| token.amount),
+			Some(brand_id) => Self::cal_sum_balance(&brand_id, who),
+			None => Zero::zero(),
+		}
+	}
+
+	fn reducible_balance(
+		asset: BrandAssetId,
+		who: &T::AccountId,
+		_keep_alive: bool,
+	) -> T::PointBalance {
+		match AssetBrands::<T>::get(asset) {
+			Some(brand_id) if FrozenAccounts::<T>::get(&brand_id, who) => Zero::zero(),
+			_ => Self::balance(asset, who),
+		}
+	}
+
+	fn can_deposit(
+		asset: BrandAssetId,
+		who: &T::AccountId,
+		amount: T::PointBalance,
+	) -> DepositConsequence {
+		let brand_id = match AssetBrands::<T>::get(asset) {
+			Some(brand_id) => brand_id,
+			None => return DepositConsequence::UnknownAsset,
+		};
+		let token = match BrandTokenById::<T>::get(&brand_id) {
+			Some(token) => token,
+			None => return DepositConsequence::UnknownAsset,
+		};
+		if token.staked.checked_add(&amount).is_none() {
+			return DepositConsequence::Overflow
+		}
+		let full = UTXO::<T>::decode_len(&brand_id, who)
+			.map_or(false, |len| len >= T::MaxUtxoEntries::get() as usize);
+		if *who != brand_id && full {
+			return DepositConsequence::CannotCreate
+		}
+		DepositConsequence::Success
+	}
+
+	fn can_withdraw(
+		asset: BrandAssetId,
+		who: &T::AccountId,
+		amount: T::PointBalance,
+	) -> WithdrawConsequence<T::PointBalance> {
+		let brand_id = match AssetBrands::<T>::get(asset) {
+			Some(brand_id) => brand_id,
+			None => return WithdrawConsequence::UnknownAsset,
+		};
+		if FrozenAccounts::<T>::get(&brand_id, who) {
+			return WithdrawConsequence::Frozen
+		}
+		if Self::balance(asset, who) < amount {
+			return WithdrawConsequence::NoFunds
+		}
+		WithdrawConsequence::Success
+	}
+}
+
+impl<T: Config> fungibles::Mutate<T::AccountId> for Pallet<T> {
+	fn mint_into(asset: BrandAssetId, who: &T::AccountId, amount: T::PointBalance) -> DispatchResult {
+		let brand_id = AssetBrands::<T>::get(asset).ok_or(Error::<T>::BrandTokenNotFound)?;
+		Self::do_mint(&brand_id, who, amount)
+	}
+
+	fn burn_from(
+		asset: BrandAssetId,
+		who: &T::AccountId,
+		amount: T::PointBalance,
+	) -> Result<T::PointBalance, DispatchError> {
+		let brand_id = AssetBrands::<T>::get(asset).ok_or(Error::<T>::BrandTokenNotFound)?;
+		Self::do_burn(&brand_id, who, amount)?;
+		Ok(amount)
+	}
+}
+
+impl<T: Config> fungibles::Transfer<T::AccountId> for Pallet<T> {
+	fn transfer(
+		asset: BrandAssetId,
+		source: &T::AccountId,
+		dest: &T::AccountId,
+		amount: T::PointBalance,
+		_keep_alive: bool,
+	) -> Result<T::PointBalance, DispatchError> {
+		let brand_id = AssetBrands::<T>::get(asset).ok_or(Error::<T>::BrandTokenNotFound)?;
+		Self::do_transfer_points(&brand_id, source, dest, amount)?;
+		Ok(amount)
+	}
+}
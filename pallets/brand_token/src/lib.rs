@@ -5,11 +5,14 @@
 /// <https://docs.substrate.io/v3/runtime/frame>
 pub use pallet::*;
 
-// #[cfg(test)]
-// mod mock;
+mod impl_fungibles;
+pub mod migrations;
 
-// #[cfg(test)]
-// mod tests;
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
 
 // #[cfg(feature = "runtime-benchmarks")]
 // mod benchmarking;
@@ -20,17 +23,69 @@ pub mod pallet {
 		pallet_prelude::{DispatchResult, *},
 		sp_runtime::traits::Saturating,
 		sp_runtime::traits::Scale,
-		sp_runtime::SaturatedConversion,
-		traits::{Currency, ReservableCurrency, Time},
+		sp_runtime::traits::{One, Zero},
+		sp_runtime::traits::{AtLeast32BitUnsigned, CheckedAdd, CheckedSub, IdentifyAccount, Verify},
+		sp_runtime::{FixedPointNumber, FixedU128, SaturatedConversion},
+		storage::StoragePrefixedMap,
+		traits::{
+			tokens::ExistenceRequirement, BalanceStatus, ConstU32, Currency, ReservableCurrency,
+			Time,
+		},
+		transactional, StorageHasher,
 	};
 	use frame_system::pallet_prelude::*;
-	use scale_info::prelude::vec;
+	use pallet_brand_admin::{BrandLifecycle, BrandRole, BrandStatus, CoalitionId, OutletId};
 	use scale_info::{StaticTypeInfo, TypeInfo};
 	use sp_std::vec::Vec;
 
 	type BalanceOf<T> =
 		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
+	pub type BrandTokenOf<T> = BrandToken<
+		<T as Config>::PointBalance,
+		BalanceOf<T>,
+		BoundedVec<u8, <T as Config>::MaxSymbolLen>,
+	>;
+	pub type AmountOf<T> = Amount<<T as Config>::PointBalance, <T as Config>::Moment>;
+	pub type UtxoOf<T> = BoundedVec<AmountOf<T>, <T as Config>::MaxUtxoEntries>;
+	pub type CampaignOf<T> = Campaign<<T as Config>::PointBalance>;
+	pub type CampaignsOf<T> = BoundedVec<(CampaignId, CampaignOf<T>), <T as Config>::MaxCampaigns>;
+	pub type HoldOf<T> = Hold<
+		<T as frame_system::Config>::AccountId,
+		<T as frame_system::Config>::BlockNumber,
+		UtxoOf<T>,
+	>;
+	pub type PendingClawbackOf<T> = PendingClawback<
+		<T as frame_system::Config>::AccountId,
+		<T as frame_system::Config>::BlockNumber,
+		UtxoOf<T>,
+		BoundedVec<u8, <T as Config>::MaxReasonLen>,
+	>;
+	pub type SponsorshipOf<T> =
+		Sponsorship<BalanceOf<T>, BoundedVec<CallIndex, <T as Config>::MaxSponsoredCalls>>;
+	pub type ReferralOf<T> =
+		Referral<<T as frame_system::Config>::AccountId, <T as Config>::PointBalance>;
+	pub type ExpirySeedCursorOf = BoundedVec<u8, ConstU32<MAX_UTXO_KEY_LEN>>;
+
+	/// Width of an `ExpiryQueue` bucket, in the same unit as `T::Moment`.
+	const EXPIRY_BUCKET: u64 = 3600;
+
+	/// Length of a raw `UTXO` key for accounts of up to 32 bytes: the 32 byte storage prefix and
+	/// two `Twox64Concat` hashed accounts. Checked against `T::AccountId` in `integrity_test`.
+	pub const MAX_UTXO_KEY_LEN: u32 = 32 + 2 * (8 + 32);
+
+	/// Tag prefixed to signed vouchers, see [`Pallet::voucher_payload`].
+	pub const VOUCHER_CONTEXT: &[u8] = b"loyavis/voucher";
+
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(7);
+
+	/// Length of a lifetime month, in the same unit as `T::Moment`.
+	const MONTH: u64 = 2592000;
+
+	/// Length of a day, in the same unit as `T::Moment`.
+	const DAY: u64 = 86400;
+
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
 	pub trait Config: frame_system::Config + pallet_brand_admin::Config {
@@ -39,6 +94,16 @@ pub mod pallet {
 
 		type Currency: ReservableCurrency<Self::AccountId> + Currency<Self::AccountId>;
 
+		/// Balance type of brand points. Each token backs its points with native currency at its
+		/// own `collateral_ratio`.
+		type PointBalance: Parameter
+			+ Member
+			+ AtLeast32BitUnsigned
+			+ Default
+			+ Copy
+			+ MaxEncodedLen
+			+ MaybeSerializeDeserialize;
+
 		type Moment: Parameter
 			+ Default
 			+ Scale<Self::BlockNumber, Output = Self::Moment>
@@ -50,34 +115,238 @@ pub mod pallet {
 			+ Into<u64>;
 
 		type Timestamp: Time<Moment = Self::Moment>;
+
+		/// Maximum length of a brand token symbol.
+		#[pallet::constant]
+		type MaxSymbolLen: Get<u32>;
+
+		/// Maximum number of point grants a holder can have in one brand token.
+		#[pallet::constant]
+		type MaxUtxoEntries: Get<u32>;
+
+		/// Signature brands use to sign earn vouchers off-chain.
+		type OffchainSignature: Verify<Signer = Self::OffchainPublic> + Parameter;
+
+		/// Public key behind `OffchainSignature`, identifying the signing brand account.
+		type OffchainPublic: IdentifyAccount<AccountId = Self::AccountId>;
+
+		/// Origin allowed to set the minimum collateral ratio.
+		type CollateralOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Maximum number of holders whose expired points are swept in one block.
+		#[pallet::constant]
+		type MaxExpiriesPerBlock: Get<u32>;
+
+		/// Maximum number of campaigns a brand can have at once.
+		#[pallet::constant]
+		type MaxCampaigns: Get<u32>;
+
+		/// Maximum length of the referrer chain above a new referral.
+		#[pallet::constant]
+		type MaxReferralDepth: Get<u32>;
+
+		/// Blocks after which held points go back to the customer unless captured.
+		#[pallet::constant]
+		type HoldTimeout: Get<Self::BlockNumber>;
+
+		/// Blocks during which captured points can be refunded.
+		#[pallet::constant]
+		type RefundPeriod: Get<Self::BlockNumber>;
+
+		/// Maximum number of timed out holds handled in one block.
+		#[pallet::constant]
+		type MaxHoldTimeoutsPerBlock: Get<u32>;
+
+		/// Origin allowed to limit clawbacks and decide on appeals against them.
+		type ClawbackOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Maximum length of a clawback reason.
+		#[pallet::constant]
+		type MaxReasonLen: Get<u32>;
+
+		/// Maximum number of calls a brand can sponsor.
+		#[pallet::constant]
+		type MaxSponsoredCalls: Get<u32>;
 	}
 
-	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
-	#[scale_info(skip_type_params(T))]
-	pub struct BrandToken {
-		pub symbol: Vec<u8>,
-		pub amount: u32,
-		pub staked: u32,
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct BrandToken<Balance, NativeBalance, Symbol> {
+		pub symbol: Symbol,
+		/// Number of decimals wallets should display amounts with.
+		pub decimals: u8,
+		pub amount: Balance,
+		pub staked: Balance,
+		/// Native currency reserved per point, in base units.
+		pub collateral_ratio: FixedU128,
+		/// Native currency reserved as collateral for the `staked` points.
+		pub collateral: NativeBalance,
+		/// Lifetime in months of grants issued without their own.
 		pub default_lifetime: u8,
+		pub expiry_policy: ExpiryPolicy,
+	}
+
+	/// How the points of a token expire.
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum ExpiryPolicy {
+		/// Each grant expires its lifetime after being issued.
+		Lifetime,
+		/// Every grant expires at the given moment.
+		FixedDate(u64),
+		/// A holder's grants expire their lifetime after the holder's last activity.
+		Rolling,
+		/// Each grant expires at the end of the calendar year in which its lifetime ends.
+		YearEnd,
+		/// Points never expire.
+		Never,
+	}
+
+	impl Default for ExpiryPolicy {
+		fn default() -> Self {
+			ExpiryPolicy::Lifetime
+		}
 	}
 
-	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
-	#[scale_info(skip_type_params(T))]
-	pub struct Amount<Time> {
-		pub amount: u32,
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct Amount<Balance, Time> {
+		pub amount: Balance,
 		pub issued_date: Time,
+		/// Lifetime of the grant in months.
+		pub lifetime: u8,
+	}
+
+	/// An earn signed off-chain by a brand, e.g. at an offline POS terminal, and submitted by
+	/// the customer through `claim_voucher`. The brand signs [`Pallet::voucher_payload`], not
+	/// the voucher alone.
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct EarnVoucher<AccountId, Balance, BlockNumber> {
+		pub brand: AccountId,
+		pub beneficiary: AccountId,
+		pub amount: Balance,
+		/// Unique per brand; a nonce can be claimed only once.
+		pub nonce: u64,
+		/// Last block at which the voucher can be claimed.
+		pub expiry: BlockNumber,
+	}
+
+	pub type EarnVoucherOf<T> = EarnVoucher<
+		<T as frame_system::Config>::AccountId,
+		<T as Config>::PointBalance,
+		<T as frame_system::Config>::BlockNumber,
+	>;
+
+	pub type CampaignId = u32;
+
+	/// A time-boxed promotion giving bonus points on every earn while it runs, e.g. double
+	/// points for a weekend or a one-off signup bonus.
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct Campaign<Balance> {
+		/// First moment the campaign applies.
+		pub start: u64,
+		/// Moment the campaign stops applying.
+		pub end: u64,
+		/// Factor applied to the earned amount, e.g. 2 for double points.
+		pub multiplier: FixedU128,
+		/// Points added to every earn.
+		pub bonus: Balance,
+		/// Most bonus points one customer can get from the campaign.
+		pub customer_cap: Balance,
+		/// Bonus points the campaign can still give out, set aside from the brand pool.
+		pub budget: Balance,
+	}
+
+	/// Id of a brand token under the `fungibles` traits.
+	pub type BrandAssetId = u32;
+
+	pub type HoldId = u64;
+
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum HoldStatus {
+		/// The points are set aside while the order settles.
+		Held,
+		/// The brand took the points and may still refund them.
+		Captured,
+	}
+
+	/// Points taken out of a customer's balance for a pending purchase.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct Hold<AccountId, BlockNumber, Grants> {
+		pub who: AccountId,
+		/// The held grants, or once captured the captured ones, with their original dates.
+		pub grants: Grants,
+		pub status: HoldStatus,
+		/// Block at which held points go back to the customer, or captured ones can no longer
+		/// be refunded.
+		pub timeout: BlockNumber,
+	}
+
+	pub type ClawbackId = u64;
+
+	/// Points a brand took back from a customer, set aside until the appeal window passed.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct PendingClawback<AccountId, BlockNumber, Grants, Reason> {
+		pub who: AccountId,
+		/// The clawed back grants with their original dates.
+		pub grants: Grants,
+		pub reason: Reason,
+		/// First block at which the brand can execute the clawback.
+		pub execute_at: BlockNumber,
+		/// The customer appealed, so `ClawbackOrigin` decides.
+		pub appealed: bool,
+	}
+
+	/// A runtime call, as its pallet index and call index.
+	pub type CallIndex = [u8; 2];
+
+	/// Fees a brand pays for its customers' transactions.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct Sponsorship<Balance, Calls> {
+		/// Native currency reserved from the brand to pay fees with.
+		pub budget: Balance,
+		/// Calls the brand pays the fees of.
+		pub calls: Calls,
+		/// Most fees paid for one customer in a day.
+		pub daily_limit: Balance,
+		/// Only customers on the brand's allowlist are sponsored.
+		pub allowlist_only: bool,
+	}
+
+	/// Who brought a customer to a brand.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct Referral<AccountId, Balance> {
+		pub referrer: AccountId,
+		/// Moment the referral was registered.
+		pub registered_at: u64,
+		/// Bonus each party got on the referee's first earn, once that happened.
+		pub reward: Option<Balance>,
+	}
+
+	/// Points of the target brand given per point of the source brand. Customers can exchange
+	/// at it only once both brands agreed on it.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct ExchangeRate {
+		pub rate: FixedU128,
+		pub from_agreed: bool,
+		pub to_agreed: bool,
+	}
+
+	/// Points a coalition member issued from, and accepted back into, the shared pool since
+	/// the last settlement.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, Default)]
+	pub struct CoalitionLedger<Balance> {
+		pub issued: Balance,
+		pub redeemed: Balance,
 	}
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
-	#[pallet::without_storage_info]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	// The pallet's runtime storage items.
 	// https://docs.substrate.io/v3/runtime/storage
 	#[pallet::storage]
 	#[pallet::getter(fn brand_token_by_id)]
-	pub type BrandTokenById<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, BrandToken>;
+	pub type BrandTokenById<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, BrandTokenOf<T>>;
 
 	#[pallet::storage]
 	#[pallet::getter(fn utxo)]
@@ -87,17 +356,395 @@ pub mod pallet {
 		T::AccountId,
 		Twox64Concat,
 		T::AccountId,
-		Vec<Amount<T::Moment>>,
+		UtxoOf<T>,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn coalition_ledger)]
+	pub type CoalitionLedgers<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		CoalitionId,
+		Twox64Concat,
+		T::AccountId,
+		CoalitionLedger<T::PointBalance>,
+		ValueQuery,
+	>;
+
+	/// Brands whose customers may transfer points to each other.
+	#[pallet::storage]
+	#[pallet::getter(fn p2p_transfer_allowed)]
+	pub type P2PTransferAllowed<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, bool, ValueQuery>;
+
+	/// Lowest collateral ratio a token can be created or minted with.
+	#[pallet::storage]
+	#[pallet::getter(fn min_collateral_ratio)]
+	pub type MinCollateralRatio<T: Config> = StorageValue<_, FixedU128, ValueQuery>;
+
+	/// Last earn, spend or transfer of each holder, per brand. Used by `ExpiryPolicy::Rolling`.
+	#[pallet::storage]
+	pub type LastActivity<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, T::AccountId, u64, ValueQuery>;
+
+	/// Holders with points expiring in a bucket of `EXPIRY_BUCKET`, keyed by bucket and then by
+	/// brand and holder.
+	#[pallet::storage]
+	pub type ExpiryQueue<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		u64,
+		Blake2_128Concat,
+		(T::AccountId, T::AccountId),
+		(),
+		OptionQuery,
+	>;
+
+	/// Oldest `ExpiryQueue` bucket that may still hold entries.
+	#[pallet::storage]
+	pub type NextExpiryBucket<T: Config> = StorageValue<_, u64, OptionQuery>;
+
+	/// Raw `UTXO` key after which holders from before the expiry queue still have to be queued,
+	/// empty to start from the first one. Removed once every holder was queued.
+	#[pallet::storage]
+	pub type ExpirySeedCursor<T: Config> = StorageValue<_, ExpirySeedCursorOf, OptionQuery>;
+
+	/// Brands whose holders still have to be queued under a changed expiry policy, with the raw
+	/// `UTXO` key to continue after. See `set_expiry_policy`.
+	#[pallet::storage]
+	pub type ExpiryRequeueCursor<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, ExpirySeedCursorOf, OptionQuery>;
+
+	/// Campaigns of each brand, running or scheduled.
+	#[pallet::storage]
+	#[pallet::getter(fn campaigns)]
+	pub type Campaigns<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, CampaignsOf<T>, ValueQuery>;
+
+	#[pallet::storage]
+	pub type NextCampaignId<T: Config> = StorageValue<_, CampaignId, ValueQuery>;
+
+	/// Bonus points each customer got from a campaign so far.
+	#[pallet::storage]
+	pub type CampaignBonuses<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		CampaignId,
+		Twox64Concat,
+		T::AccountId,
+		T::PointBalance,
+		ValueQuery,
+	>;
+
+	/// Points the brand gives both the referrer and the referee on the referee's first earn.
+	#[pallet::storage]
+	#[pallet::getter(fn referral_bonus)]
+	pub type ReferralBonus<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, T::PointBalance, ValueQuery>;
+
+	/// Referral of each customer, per brand and referee.
+	#[pallet::storage]
+	#[pallet::getter(fn referral)]
+	pub type Referrals<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, T::AccountId, ReferralOf<T>>;
+
+	/// Number of rewarded referrals of each referrer, per brand.
+	#[pallet::storage]
+	#[pallet::getter(fn referral_count)]
+	pub type ReferralCounts<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+	/// Customers a brand has ever issued points to, kept after their points expire.
+	#[pallet::storage]
+	#[pallet::getter(fn has_earned)]
+	pub type HasEarned<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Twox64Concat,
+		T::AccountId,
+		bool,
+		ValueQuery,
+	>;
+
+	/// Exchange rates, keyed by source and then target brand.
+	#[pallet::storage]
+	#[pallet::getter(fn exchange_rate)]
+	pub type ExchangeRates<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, T::AccountId, ExchangeRate>;
+
+	/// Index of `ExchangeRates` by target and then source brand.
+	#[pallet::storage]
+	pub type ExchangeRatesInto<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, T::AccountId, ()>;
+
+	/// Most points of a brand that can be exchanged, into or out of it, in a day. Zero, the
+	/// default, keeps the brand out of exchanges.
+	#[pallet::storage]
+	#[pallet::getter(fn exchange_cap)]
+	pub type ExchangeCaps<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, T::PointBalance, ValueQuery>;
+
+	/// Day and volume of a brand's latest exchanges.
+	#[pallet::storage]
+	pub type ExchangeVolume<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, (u64, T::PointBalance), ValueQuery>;
+
+	/// Holds of each brand, by hold id.
+	#[pallet::storage]
+	#[pallet::getter(fn hold)]
+	pub type Holds<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, HoldId, HoldOf<T>>;
+
+	#[pallet::storage]
+	pub type NextHoldId<T: Config> = StorageValue<_, HoldId, ValueQuery>;
+
+	/// Holds timing out at a block, keyed by block and then by brand and hold id.
+	#[pallet::storage]
+	pub type HoldTimeouts<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		Blake2_128Concat,
+		(T::AccountId, HoldId),
+		(),
+		OptionQuery,
+	>;
+
+	/// Oldest `HoldTimeouts` block that may still hold entries.
+	#[pallet::storage]
+	pub type NextHoldTimeout<T: Config> = StorageValue<_, T::BlockNumber, OptionQuery>;
+
+	/// Customers whose points in a brand can't be spent, moved or refunded.
+	#[pallet::storage]
+	#[pallet::getter(fn is_frozen)]
+	pub type FrozenAccounts<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, T::AccountId, bool, ValueQuery>;
+
+	/// Most points a brand can claw back from one customer in total. No cap when unset.
+	#[pallet::storage]
+	#[pallet::getter(fn clawback_cap)]
+	pub type ClawbackCap<T: Config> = StorageValue<_, T::PointBalance, OptionQuery>;
+
+	/// Blocks a customer has to appeal a clawback before the brand can execute it. Clawbacks
+	/// are immediate when zero.
+	#[pallet::storage]
+	#[pallet::getter(fn clawback_appeal_window)]
+	pub type ClawbackAppealWindow<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+
+	/// Points each brand clawed back, or is clawing back, from each customer.
+	#[pallet::storage]
+	#[pallet::getter(fn clawed_back)]
+	pub type ClawedBack<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Twox64Concat,
+		T::AccountId,
+		T::PointBalance,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn pending_clawback)]
+	pub type PendingClawbacks<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, ClawbackId, PendingClawbackOf<T>>;
+
+	#[pallet::storage]
+	pub type NextClawbackId<T: Config> = StorageValue<_, ClawbackId, ValueQuery>;
+
+	/// Points a brand takes per native base unit of transaction fee it pays for its customers.
+	#[pallet::storage]
+	#[pallet::getter(fn fee_rate)]
+	pub type FeeRates<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, FixedU128>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn sponsorship)]
+	pub type Sponsorships<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, SponsorshipOf<T>>;
+
+	/// Customers a brand sponsors when its sponsorship is limited to an allowlist.
+	#[pallet::storage]
+	#[pallet::getter(fn sponsor_allowlist)]
+	pub type SponsorAllowlist<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, T::AccountId, bool, ValueQuery>;
+
+	/// Day and amount of the fees a brand last paid for each customer.
+	#[pallet::storage]
+	pub type SponsoredFees<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Twox64Concat,
+		T::AccountId,
+		(u64, BalanceOf<T>),
+		ValueQuery,
 	>;
 
+	/// Asset id of each brand token.
+	#[pallet::storage]
+	#[pallet::getter(fn asset_id)]
+	pub type AssetIds<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, BrandAssetId>;
+
+	/// Brand of each asset id.
+	#[pallet::storage]
+	#[pallet::getter(fn asset_brand)]
+	pub type AssetBrands<T: Config> = StorageMap<_, Twox64Concat, BrandAssetId, T::AccountId>;
+
+	#[pallet::storage]
+	pub type NextAssetId<T: Config> = StorageValue<_, BrandAssetId, ValueQuery>;
+
+	/// Voucher nonces already claimed, per brand. Kept when the brand is removed, so its
+	/// vouchers can't be claimed again should the account register a brand anew.
+	#[pallet::storage]
+	pub type UsedVoucherNonces<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, u64, (), OptionQuery>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		BrandTokenCreated { brand_id: T::AccountId },
-		Mint { amount: u32 },
-		Burn { amount: u32 },
-		Transferred { amount: u32, from: T::AccountId, to: T::AccountId },
-		Earned { amount: u32, issued_date: T::Moment },
+		BrandTokenCreated { brand_id: T::AccountId, asset_id: BrandAssetId },
+		Mint { amount: T::PointBalance },
+		Burn { amount: T::PointBalance },
+		Transferred { amount: T::PointBalance, from: T::AccountId, to: T::AccountId },
+		/// Points were earned: `amount` is the base amount and `bonus` what campaigns and the
+		/// referral bonus added.
+		Earned {
+			amount: T::PointBalance,
+			bonus: T::PointBalance,
+			issued_date: T::Moment,
+			outlet: Option<OutletId>,
+		},
+		/// A holder gave back points of a defaulted brand for their share of its collateral.
+		Refunded {
+			brand_id: T::AccountId,
+			who: T::AccountId,
+			amount: T::PointBalance,
+			collateral: BalanceOf<T>,
+		},
+		/// The brand was removed; its stake was released and outstanding points burned.
+		BrandTokenDestroyed { brand_id: T::AccountId, burned: T::PointBalance },
+		CoalitionFunded { coalition_id: CoalitionId, brand_id: T::AccountId, amount: T::PointBalance },
+		CoalitionEarned {
+			coalition_id: CoalitionId,
+			brand_id: T::AccountId,
+			who: T::AccountId,
+			amount: T::PointBalance,
+		},
+		CoalitionRedeemed {
+			coalition_id: CoalitionId,
+			brand_id: T::AccountId,
+			who: T::AccountId,
+			amount: T::PointBalance,
+		},
+		/// A member settled its net position: `paid` went into the coalition account when it
+		/// issued more than it redeemed, `received` came out of it otherwise.
+		CoalitionSettled { coalition_id: CoalitionId, brand_id: T::AccountId, paid: T::PointBalance, received: T::PointBalance },
+		/// A brand-signed voucher was claimed and its points credited.
+		VoucherClaimed { brand_id: T::AccountId, who: T::AccountId, nonce: u64, amount: T::PointBalance },
+		/// The brand allowed or forbade point transfers between its customers.
+		P2PTransferSet { brand_id: T::AccountId, allowed: bool },
+		/// The brand changed the collateral ratio of its token and re-balanced its reserve.
+		CollateralRatioSet { brand_id: T::AccountId, ratio: FixedU128 },
+		/// Governance changed the minimum collateral ratio.
+		MinCollateralRatioSet { ratio: FixedU128 },
+		/// Expired points of `who` went back to the brand pool.
+		PointsExpired { brand: T::AccountId, who: T::AccountId, amount: T::PointBalance },
+		/// The brand changed the lifetime of future grants.
+		DefaultLifetimeSet { brand_id: T::AccountId, lifetime: u8 },
+		/// The brand changed how its points expire.
+		ExpiryPolicySet { brand_id: T::AccountId, policy: ExpiryPolicy },
+		CampaignCreated { brand_id: T::AccountId, campaign_id: CampaignId },
+		/// The campaign was ended and its remaining budget went back to the brand pool.
+		CampaignEnded { brand_id: T::AccountId, campaign_id: CampaignId, refunded: T::PointBalance },
+		/// A campaign added `bonus` points to an earn of `base` points by `who`.
+		CampaignBonusGranted {
+			brand_id: T::AccountId,
+			campaign_id: CampaignId,
+			who: T::AccountId,
+			base: T::PointBalance,
+			bonus: T::PointBalance,
+		},
+		ReferralBonusSet { brand_id: T::AccountId, bonus: T::PointBalance },
+		ReferrerRegistered { brand_id: T::AccountId, referee: T::AccountId, referrer: T::AccountId },
+		/// The referee earned for the first time and both parties got `bonus` points.
+		ReferralRewarded {
+			brand_id: T::AccountId,
+			referee: T::AccountId,
+			referrer: T::AccountId,
+			bonus: T::PointBalance,
+		},
+		/// One of the brands set or agreed on the rate from `from` to `to`. `agreed` tells
+		/// whether both brands now agree on it.
+		ExchangeRateSet { from: T::AccountId, to: T::AccountId, rate: FixedU128, agreed: bool },
+		ExchangeRateWithdrawn { from: T::AccountId, to: T::AccountId },
+		ExchangeCapSet { brand_id: T::AccountId, cap: T::PointBalance },
+		/// `who` gave `amount` points of `from` for `received` points of `to`.
+		Exchanged {
+			who: T::AccountId,
+			from: T::AccountId,
+			to: T::AccountId,
+			amount: T::PointBalance,
+			received: T::PointBalance,
+		},
+		PointsHeld {
+			brand_id: T::AccountId,
+			hold_id: HoldId,
+			who: T::AccountId,
+			amount: T::PointBalance,
+			timeout: T::BlockNumber,
+		},
+		/// Held points went back to the customer.
+		HoldReleased { brand_id: T::AccountId, hold_id: HoldId, amount: T::PointBalance },
+		/// The brand took `captured` held points into its pool; `released` went back to the
+		/// customer.
+		HoldCaptured {
+			brand_id: T::AccountId,
+			hold_id: HoldId,
+			captured: T::PointBalance,
+			released: T::PointBalance,
+		},
+		/// The brand gave back `amount` captured points, with their original expiry.
+		HoldRefunded { brand_id: T::AccountId, hold_id: HoldId, amount: T::PointBalance },
+		/// The refund period of a captured hold ended.
+		HoldClosed { brand_id: T::AccountId, hold_id: HoldId },
+		AccountFrozen { brand_id: T::AccountId, who: T::AccountId },
+		AccountThawed { brand_id: T::AccountId, who: T::AccountId },
+		ClawbackLimitsSet { cap: Option<T::PointBalance>, appeal_window: T::BlockNumber },
+		/// The brand took `amount` points of `who` back into its pool.
+		ClawedBack {
+			brand_id: T::AccountId,
+			who: T::AccountId,
+			amount: T::PointBalance,
+			reason: Vec<u8>,
+		},
+		/// The brand set `amount` points of `who` aside to claw back once the appeal window
+		/// passes.
+		ClawbackScheduled {
+			brand_id: T::AccountId,
+			clawback_id: ClawbackId,
+			who: T::AccountId,
+			amount: T::PointBalance,
+			execute_at: T::BlockNumber,
+		},
+		ClawbackAppealed { brand_id: T::AccountId, clawback_id: ClawbackId },
+		/// The appeal was upheld and the points went back to the customer.
+		ClawbackReversed { brand_id: T::AccountId, clawback_id: ClawbackId, amount: T::PointBalance },
+		/// The brand set, or with `None` stopped, the rate at which it pays fees for points.
+		FeeRateSet { brand_id: T::AccountId, rate: Option<FixedU128> },
+		/// `who` paid a transaction fee of `fee` with `points` points, the brand's stake paying
+		/// the fee.
+		FeePaidInPoints {
+			brand_id: T::AccountId,
+			who: T::AccountId,
+			points: T::PointBalance,
+			fee: BalanceOf<T>,
+		},
+		SponsorshipSet { brand_id: T::AccountId },
+		SponsorshipFunded { brand_id: T::AccountId, amount: BalanceOf<T> },
+		SponsorshipWithdrawn { brand_id: T::AccountId, amount: BalanceOf<T> },
+		/// The brand ended its sponsorship and got `refunded` of its budget back.
+		SponsorshipEnded { brand_id: T::AccountId, refunded: BalanceOf<T> },
+		SponsorAllowlistSet { brand_id: T::AccountId, who: T::AccountId, allowed: bool },
+		/// The brand paid a transaction fee of `fee` for `who`.
+		FeeSponsored { brand_id: T::AccountId, who: T::AccountId, fee: BalanceOf<T> },
 	}
 
 	#[pallet::error]
@@ -113,50 +760,189 @@ pub mod pallet {
 		InsufficentBalance,
 		InvalidAmount,
 		NotSupportedYet,
+		/// The staked reserve could not be moved to the new brand owner.
+		StakeNotMoved,
+		/// Refunds are only possible while the brand is winding down or suspended.
+		BrandNotWindingDown,
+		/// The coalition does not have a token yet.
+		CoalitionTokenNotFound,
+		/// The symbol is longer than `MaxSymbolLen`.
+		SymbolTooLong,
+		/// The holder already has `MaxUtxoEntries` grants in this token.
+		TooManyUtxoEntries,
+		/// The voucher is not signed by its brand.
+		InvalidVoucherSignature,
+		/// The voucher expiry block has passed.
+		VoucherExpired,
+		/// The voucher nonce was already claimed.
+		VoucherAlreadyClaimed,
+		/// The brand does not allow transfers between customers.
+		P2PTransferDisabled,
+		/// Points can't be transferred to their current holder.
+		TransferToSelf,
+		/// The collateral ratio is below `MinCollateralRatio`.
+		CollateralRatioTooLow,
+		/// The campaign ends before it starts, has already ended, or has a multiplier below one.
+		InvalidCampaign,
+		/// The brand already has `MaxCampaigns` campaigns.
+		TooManyCampaigns,
+		CampaignNotFound,
+		/// Customers can't refer themselves.
+		SelfReferral,
+		/// The customer already has a referrer in this brand.
+		ReferrerAlreadySet,
+		/// The customer already earned in this brand, so can no longer be referred.
+		AlreadyEarned,
+		/// The referrer was itself referred, directly or not, by the customer.
+		ReferralCycle,
+		/// The referrer chain is longer than `MaxReferralDepth`.
+		ReferralChainTooLong,
+		/// Points can't be exchanged into the same brand.
+		SameBrand,
+		/// The brand is neither side of the exchange rate.
+		NotExchangeParty,
+		/// Both brands have not agreed on an exchange rate between them.
+		ExchangeRateNotAgreed,
+		/// The exchange would take a brand over its daily exchange cap.
+		ExchangeCapExceeded,
+		HoldNotFound,
+		/// The hold was already captured.
+		HoldNotHeld,
+		/// Only captured holds can be refunded.
+		HoldNotCaptured,
+		/// The amount is more than the hold has left.
+		HoldAmountTooHigh,
+		/// The brand froze the customer's points.
+		AccountFrozen,
+		/// The reason is longer than `MaxReasonLen`.
+		ReasonTooLong,
+		/// The clawback would take the brand over `ClawbackCap` for this customer.
+		ClawbackCapExceeded,
+		ClawbackNotFound,
+		/// Only the customer a clawback is against can appeal it.
+		NotClawbackSubject,
+		/// The appeal window of the clawback has passed.
+		AppealWindowClosed,
+		/// The appeal window of the clawback is still open.
+		AppealWindowOpen,
+		/// The clawback was appealed and waits for a decision.
+		ClawbackAppealed,
+		/// The clawback was not appealed.
+		ClawbackNotAppealed,
+		/// The brand does not sponsor fees.
+		SponsorshipNotFound,
+		/// More calls than `MaxSponsoredCalls`.
+		TooManySponsoredCalls,
+		/// The sponsorship does not cover the transaction, or its budget or the customer's daily
+		/// limit ran out.
+		SponsorshipExhausted,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			crate::migrations::v1::migrate::<T>()
+				.saturating_add(crate::migrations::v2::migrate::<T>())
+				.saturating_add(crate::migrations::v3::migrate::<T>())
+				.saturating_add(crate::migrations::v4::migrate::<T>())
+				.saturating_add(crate::migrations::v5::migrate::<T>())
+				.saturating_add(crate::migrations::v6::migrate::<T>())
+				.saturating_add(crate::migrations::v7::migrate::<T>())
+		}
+
+		fn on_idle(n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			let used = Self::seed_expiry_queue(remaining_weight);
+			let used = used
+				.saturating_add(Self::requeue_expiries(remaining_weight.saturating_sub(used)));
+			let used = used
+				.saturating_add(Self::process_expiry_queue(remaining_weight.saturating_sub(used)));
+			used.saturating_add(Self::process_hold_timeouts(n, remaining_weight.saturating_sub(used)))
+		}
+
+		fn integrity_test() {
+			assert!(
+				32 + 2 * (8 + T::AccountId::max_encoded_len()) <= MAX_UTXO_KEY_LEN as usize,
+				"raw `UTXO` keys must fit into `ExpirySeedCursorOf`",
+			);
+		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(4))]
+		#[transactional]
 		pub fn create_new_token(
 			origin: OriginFor<T>,
 			symbol: Vec<u8>,
-			staked_amount: u32,
+			decimals: u8,
+			staked_amount: T::PointBalance,
+			collateral_ratio: FixedU128,
 			default_lifetime: u8,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 			let brand = pallet_brand_admin::Pallet::<T>::brand_by_id(&sender);
 			ensure!(brand != None, Error::<T>::BrandNotExist);
+			pallet_brand_admin::Pallet::<T>::ensure_brand_active(&sender)?;
 			ensure!(!BrandTokenById::<T>::contains_key(&sender), Error::<T>::AlreadyCreatedToken);
 
-			T::Currency::reserve(&sender, Self::u32_to_balance(staked_amount.clone()))?;
+			let symbol: BoundedVec<u8, T::MaxSymbolLen> =
+				symbol.try_into().map_err(|_| Error::<T>::SymbolTooLong)?;
+
+			ensure!(
+				collateral_ratio >= MinCollateralRatio::<T>::get(),
+				Error::<T>::CollateralRatioTooLow
+			);
+
+			let collateral = Self::collateral_for(collateral_ratio, staked_amount)?;
+			T::Currency::reserve(&sender, collateral)?;
 
 			let new_token = BrandToken {
 				symbol,
+				decimals,
 				amount: staked_amount,
 				staked: staked_amount,
+				collateral_ratio,
+				collateral,
 				default_lifetime,
+				expiry_policy: ExpiryPolicy::Lifetime,
 			};
 
-			BrandTokenById::<T>::insert(&sender, new_token.clone());
+			BrandTokenById::<T>::insert(&sender, new_token);
+			let asset_id = Self::register_asset(&sender)?;
 
-			Self::deposit_event(Event::BrandTokenCreated { brand_id: sender });
+			Self::deposit_event(Event::BrandTokenCreated { brand_id: sender, asset_id });
 
 			Ok(())
 		}
 
+		/// Mint more brand tokens by reserving native currency from the brand account at the
+		/// token's collateral ratio. Callable by the brand or one of its `Minter` operators.
 		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn mint(origin: OriginFor<T>, amount: u32) -> DispatchResult {
+		pub fn mint(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Minter)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_active(&brand_id)?;
 
 			let mut brand_token =
-				BrandTokenById::<T>::get(&sender).ok_or(Error::<T>::BrandTokenNotFound)?;
-			brand_token.staked = brand_token.staked + amount;
-			brand_token.amount = brand_token.amount + amount;
+				BrandTokenById::<T>::get(&brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			ensure!(
+				brand_token.collateral_ratio >= MinCollateralRatio::<T>::get(),
+				Error::<T>::CollateralRatioTooLow
+			);
+			let collateral = Self::collateral_for(brand_token.collateral_ratio, amount)?;
+			brand_token.staked =
+				brand_token.staked.checked_add(&amount).ok_or(Error::<T>::StorageOverflow)?;
+			brand_token.amount =
+				brand_token.amount.checked_add(&amount).ok_or(Error::<T>::StorageOverflow)?;
+			brand_token.collateral = brand_token.collateral.saturating_add(collateral);
 
-			T::Currency::reserve(&sender, Self::u32_to_balance(amount.clone()))?;
+			T::Currency::reserve(&brand_id, collateral)?;
 
-			BrandTokenById::<T>::insert(&sender, brand_token);
+			BrandTokenById::<T>::insert(&brand_id, brand_token);
 
 			Self::deposit_event(Event::Mint { amount });
 
@@ -164,17 +950,19 @@ pub mod pallet {
 		}
 
 		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn burn(origin: OriginFor<T>, amount: u32) -> DispatchResult {
+		pub fn burn(origin: OriginFor<T>, amount: T::PointBalance) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 
 			let mut brand_token =
 				BrandTokenById::<T>::get(&sender).ok_or(Error::<T>::BrandTokenNotFound)?;
-			ensure!(amount <= brand_token.amount, Error::<T>::InsufficentAmount);
-
-			brand_token.staked = brand_token.staked - amount;
-			brand_token.amount = brand_token.amount - amount;
+			let collateral = Self::pro_rata(brand_token.collateral, amount, brand_token.staked);
+			brand_token.amount =
+				brand_token.amount.checked_sub(&amount).ok_or(Error::<T>::InsufficentAmount)?;
+			brand_token.staked =
+				brand_token.staked.checked_sub(&amount).ok_or(Error::<T>::InsufficentAmount)?;
+			brand_token.collateral = brand_token.collateral.saturating_sub(collateral);
 
-			T::Currency::unreserve(&sender, Self::u32_to_balance(amount.clone()));
+			T::Currency::unreserve(&sender, collateral);
 
 			BrandTokenById::<T>::insert(&sender, brand_token);
 
@@ -183,98 +971,2422 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn earn(origin: OriginFor<T>, amount: u32, brand_id: T::AccountId) -> DispatchResult {
+		/// Credit `beneficiary` with `amount` points from the brand pool, living `lifetime` months
+		/// or the token default. Only the brand, one of its `Cashier` operators, or the POS
+		/// operator of `outlet` may issue points.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 2))]
+		pub fn earn(
+			origin: OriginFor<T>,
+			amount: T::PointBalance,
+			brand_id: T::AccountId,
+			beneficiary: T::AccountId,
+			outlet: Option<OutletId>,
+			lifetime: Option<u8>,
+		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
-			let mut utxo = UTXO::<T>::get(&brand_id, &sender).unwrap_or(vec![]);
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role_at_outlet(
+				&brand_id,
+				&sender,
+				BrandRole::Cashier,
+				outlet,
+			)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_active(&brand_id)?;
 
-			let mut brand_token = BrandTokenById::<T>::get(&brand_id).unwrap();
-			brand_token.amount = brand_token.amount - amount;
-			BrandTokenById::<T>::insert(&brand_id, brand_token);
+			Self::issue_points(&brand_id, &beneficiary, amount, outlet, lifetime)
+		}
 
-			let new_amount = Amount { amount, issued_date: T::Timestamp::now() };
-			utxo.push(new_amount);
+		/// Claim the points of a voucher signed off-chain by the brand. Anyone may submit it and
+		/// pay the fee; the points go to the voucher beneficiary.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 3))]
+		pub fn claim_voucher(
+			origin: OriginFor<T>,
+			voucher: EarnVoucherOf<T>,
+			signature: T::OffchainSignature,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			ensure!(
+				signature.verify(&Self::voucher_payload(&voucher)[..], &voucher.brand),
+				Error::<T>::InvalidVoucherSignature
+			);
+			ensure!(
+				voucher.expiry >= frame_system::Pallet::<T>::block_number(),
+				Error::<T>::VoucherExpired
+			);
+			ensure!(
+				!UsedVoucherNonces::<T>::contains_key(&voucher.brand, voucher.nonce),
+				Error::<T>::VoucherAlreadyClaimed
+			);
+			pallet_brand_admin::Pallet::<T>::ensure_brand_active(&voucher.brand)?;
 
-			UTXO::<T>::insert(brand_id, &sender, utxo);
+			Self::issue_points(&voucher.brand, &voucher.beneficiary, voucher.amount, None, None)?;
+			UsedVoucherNonces::<T>::insert(&voucher.brand, voucher.nonce, ());
 
-			Self::deposit_event(Event::Earned { amount, issued_date: T::Timestamp::now() });
+			Self::deposit_event(Event::VoucherClaimed {
+				brand_id: voucher.brand,
+				who: voucher.beneficiary,
+				nonce: voucher.nonce,
+				amount: voucher.amount,
+			});
 
 			Ok(())
 		}
-	}
 
-	impl<T: Config> Pallet<T> {
-		pub fn u32_to_balance(input: u32) -> BalanceOf<T> {
-			input.into()
-		}
+		/// Change the collateral ratio of the brand's token. The reserve backing the outstanding
+		/// points is topped up or released to match.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		#[transactional]
+		pub fn set_collateral_ratio(origin: OriginFor<T>, ratio: FixedU128) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(ratio >= MinCollateralRatio::<T>::get(), Error::<T>::CollateralRatioTooLow);
+			pallet_brand_admin::Pallet::<T>::ensure_brand_active(&sender)?;
 
-		pub fn cal_sum_balance(brand_id: &T::AccountId, user_id: &T::AccountId) -> u32 {
-			let utxo = UTXO::<T>::get(brand_id, user_id).ok_or(Error::<T>::InsufficentBalance);
-			let sum = utxo.unwrap_or(vec![]).iter().map(|x| x.amount).sum();
-			sum
+			let mut token =
+				BrandTokenById::<T>::get(&sender).ok_or(Error::<T>::BrandTokenNotFound)?;
+			let collateral = Self::collateral_for(ratio, token.staked)?;
+			if collateral > token.collateral {
+				T::Currency::reserve(&sender, collateral - token.collateral)?;
+			} else {
+				T::Currency::unreserve(&sender, token.collateral - collateral);
+			}
+			token.collateral_ratio = ratio;
+			token.collateral = collateral;
+			BrandTokenById::<T>::insert(&sender, token);
+
+			Self::deposit_event(Event::CollateralRatioSet { brand_id: sender, ratio });
+
+			Ok(())
 		}
-	}
 
-	pub trait BrandTransferToken<AccountId> {
-		fn do_transfer(
-			from: AccountId,
-			to: AccountId,
-			brand_id: AccountId,
-			amount: u32,
-		) -> DispatchResult;
-	}
+		/// Set the lowest collateral ratio tokens can be created or minted with.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_min_collateral_ratio(origin: OriginFor<T>, ratio: FixedU128) -> DispatchResult {
+			T::CollateralOrigin::ensure_origin(origin)?;
 
-	impl<T: Config> BrandTransferToken<T::AccountId> for Pallet<T> {
-		fn do_transfer(
-			from: T::AccountId,
-			to: T::AccountId,
+			MinCollateralRatio::<T>::put(ratio);
+
+			Self::deposit_event(Event::MinCollateralRatioSet { ratio });
+
+			Ok(())
+		}
+
+		/// Change the lifetime, in months, of grants issued from now on without their own.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_default_lifetime(
+			origin: OriginFor<T>,
 			brand_id: T::AccountId,
-			amount: u32,
+			lifetime: u8,
 		) -> DispatchResult {
-			let brand_token = BrandTokenById::<T>::get(&to);
-			let mut utxo = UTXO::<T>::get(&brand_id, &from)
-				.ok_or(Error::<T>::InsufficentBalance)
-				.unwrap_or(vec![]);
-			let sum = Self::cal_sum_balance(&brand_id, &from);
-			ensure!(amount <= sum, Error::<T>::InsufficentBalance);
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
 
-			if brand_token == None {
-				// pending
-				ensure!(1 == 0, Error::<T>::NotSupportedYet);
-			} else {
-				let mut token = brand_token.clone().unwrap();
-				let now = T::Timestamp::now();
-				let now_u64 = now.saturated_into::<u64>();
-
-				let mut tmp_amount = amount;
-				for item in utxo.iter_mut() {
-					// over 30days * default_lifetime (months) -> remove
-					if now_u64.saturating_sub(item.issued_date.saturated_into::<u64>())
-						>= 2592000.saturating_mul(token.default_lifetime.into())
-					{
-						// return expired utxo to brand
-						token.amount = token.clone().amount + item.amount;
-						BrandTokenById::<T>::insert(&brand_id, token.clone());
-						
-						item.amount = 0;
-						continue;
-					}
-					if item.amount >= tmp_amount {
-						item.amount = item.amount - tmp_amount;
-						tmp_amount = 0;
-					} else if item.amount < tmp_amount {
-						tmp_amount = tmp_amount - item.amount;
-						item.amount = 0;
-					}
-				}
-				token.amount = token.amount + amount;
-				BrandTokenById::<T>::insert(&brand_id, token.clone());
+			let mut token =
+				BrandTokenById::<T>::get(&brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			token.default_lifetime = lifetime;
+			BrandTokenById::<T>::insert(&brand_id, token);
+
+			Self::deposit_event(Event::DefaultLifetimeSet { brand_id, lifetime });
+
+			Ok(())
+		}
+
+		/// Change how the brand's points expire. Applies to existing grants as well, `on_idle`
+		/// queues the holders of the brand again under the new policy over the next blocks.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2))]
+		pub fn set_expiry_policy(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			policy: ExpiryPolicy,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
 
-				utxo.retain(|x| x.amount != 0);
-				UTXO::<T>::insert(brand_id, &from, utxo);
+			let mut token =
+				BrandTokenById::<T>::get(&brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			token.expiry_policy = policy;
+			BrandTokenById::<T>::insert(&brand_id, token);
+			let mut start = UTXO::<T>::final_prefix().to_vec();
+			start.extend(Twox64Concat::hash(&brand_id.encode()));
+			// always fits, see `integrity_test`
+			if let Ok(start) = ExpirySeedCursorOf::try_from(start) {
+				ExpiryRequeueCursor::<T>::insert(&brand_id, start);
 			}
 
-			Self::deposit_event(Event::Transferred { amount, from, to });
+			Self::deposit_event(Event::ExpiryPolicySet { brand_id, policy });
+
+			Ok(())
+		}
+
+		/// Gift `amount` of the sender's points in `brand_id` to another customer. The points keep
+		/// their original issue date.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 3))]
+		#[transactional]
+		pub fn transfer(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			to: T::AccountId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(sender != to, Error::<T>::TransferToSelf);
+			ensure!(!amount.is_zero(), Error::<T>::InvalidAmount);
+			ensure!(P2PTransferAllowed::<T>::get(&brand_id), Error::<T>::P2PTransferDisabled);
+			pallet_brand_admin::Pallet::<T>::ensure_not_suspended(&brand_id)?;
+
+			Self::move_points(&brand_id, &sender, &to, amount)?;
+
+			Self::deposit_event(Event::Transferred { amount, from: sender, to });
+
+			Ok(())
+		}
+
+		/// Allow or forbid point transfers between the brand's customers.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_p2p_transfer(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			allowed: bool,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+			ensure!(BrandTokenById::<T>::contains_key(&brand_id), Error::<T>::BrandTokenNotFound);
+
+			if allowed {
+				P2PTransferAllowed::<T>::insert(&brand_id, true);
+			} else {
+				P2PTransferAllowed::<T>::remove(&brand_id);
+			}
+
+			Self::deposit_event(Event::P2PTransferSet { brand_id, allowed });
+
+			Ok(())
+		}
+
+		/// Start a campaign for the brand, setting its budget aside from the brand pool. Campaigns
+		/// that have ended are cleaned up first.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+		pub fn create_campaign(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			campaign: CampaignOf<T>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_active(&brand_id)?;
+			let now = T::Timestamp::now().saturated_into::<u64>();
+			ensure!(
+				campaign.start < campaign.end &&
+					campaign.end > now && campaign.multiplier >= FixedU128::one(),
+				Error::<T>::InvalidCampaign
+			);
+
+			let mut token =
+				BrandTokenById::<T>::get(&brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			let mut campaigns = Campaigns::<T>::get(&brand_id).into_inner();
+			Self::prune_campaigns(&mut token, &mut campaigns, now);
+			token.amount =
+				token.amount.checked_sub(&campaign.budget).ok_or(Error::<T>::InsufficentAmount)?;
+
+			let campaign_id = NextCampaignId::<T>::get();
+			let next_id = campaign_id.checked_add(1).ok_or(Error::<T>::StorageOverflow)?;
+			campaigns.push((campaign_id, campaign));
+			let campaigns =
+				CampaignsOf::<T>::try_from(campaigns).map_err(|_| Error::<T>::TooManyCampaigns)?;
+
+			NextCampaignId::<T>::put(next_id);
+			Campaigns::<T>::insert(&brand_id, campaigns);
+			BrandTokenById::<T>::insert(&brand_id, token);
+
+			Self::deposit_event(Event::CampaignCreated { brand_id, campaign_id });
+
+			Ok(())
+		}
+
+		/// Set the points both parties get on a referee's first earn. Zero turns referral rewards
+		/// off.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_referral_bonus(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			bonus: T::PointBalance,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+			ensure!(BrandTokenById::<T>::contains_key(&brand_id), Error::<T>::BrandTokenNotFound);
+
+			ReferralBonus::<T>::insert(&brand_id, bonus);
+
+			Self::deposit_event(Event::ReferralBonusSet { brand_id, bonus });
+
+			Ok(())
+		}
+
+		/// Register `referrer` as the one who brought the sender to `brand_id`. This must happen
+		/// before the sender first earns in the brand.
+		#[pallet::weight(
+			10_000 + T::DbWeight::get().reads_writes(4 + T::MaxReferralDepth::get() as u64, 1)
+		)]
+		pub fn register_referrer(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			referrer: T::AccountId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(sender != referrer, Error::<T>::SelfReferral);
+			ensure!(BrandTokenById::<T>::contains_key(&brand_id), Error::<T>::BrandTokenNotFound);
+			ensure!(
+				!Referrals::<T>::contains_key(&brand_id, &sender),
+				Error::<T>::ReferrerAlreadySet
+			);
+			ensure!(
+				!HasEarned::<T>::get(&brand_id, &sender) &&
+					!UTXO::<T>::contains_key(&brand_id, &sender) &&
+					!LastActivity::<T>::contains_key(&brand_id, &sender),
+				Error::<T>::AlreadyEarned
+			);
+
+			// walk up the referrer chain, which must not lead back to the sender
+			let mut depth = 0;
+			let mut above = Referrals::<T>::get(&brand_id, &referrer);
+			while let Some(referral) = above {
+				ensure!(referral.referrer != sender, Error::<T>::ReferralCycle);
+				depth += 1;
+				ensure!(depth < T::MaxReferralDepth::get(), Error::<T>::ReferralChainTooLong);
+				above = Referrals::<T>::get(&brand_id, &referral.referrer);
+			}
+
+			let referral = Referral {
+				referrer: referrer.clone(),
+				registered_at: T::Timestamp::now().saturated_into(),
+				reward: None,
+			};
+			Referrals::<T>::insert(&brand_id, &sender, referral);
+
+			Self::deposit_event(Event::ReferrerRegistered { brand_id, referee: sender, referrer });
+
+			Ok(())
+		}
+
+		/// Set the rate from `from` to `to` on behalf of `brand_id`, one of the two. Setting the
+		/// rate the other brand set agrees on it; setting another one replaces it and awaits the
+		/// other brand's agreement.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 1))]
+		pub fn set_exchange_rate(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			from: T::AccountId,
+			to: T::AccountId,
+			rate: FixedU128,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+			ensure!(from != to, Error::<T>::SameBrand);
+			ensure!(brand_id == from || brand_id == to, Error::<T>::NotExchangeParty);
+			ensure!(!rate.is_zero(), Error::<T>::InvalidAmount);
+
+			let mut exchange_rate = match ExchangeRates::<T>::get(&from, &to) {
+				Some(current) if current.rate == rate => current,
+				_ => ExchangeRate { rate, from_agreed: false, to_agreed: false },
+			};
+			if brand_id == from {
+				exchange_rate.from_agreed = true;
+			} else {
+				exchange_rate.to_agreed = true;
+			}
+			let agreed = exchange_rate.from_agreed && exchange_rate.to_agreed;
+			ExchangeRates::<T>::insert(&from, &to, exchange_rate);
+			ExchangeRatesInto::<T>::insert(&to, &from, ());
+
+			Self::deposit_event(Event::ExchangeRateSet { from, to, rate, agreed });
+
+			Ok(())
+		}
+
+		/// Withdraw the rate from `from` to `to` on behalf of `brand_id`, one of the two.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn withdraw_exchange_rate(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			from: T::AccountId,
+			to: T::AccountId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+			ensure!(brand_id == from || brand_id == to, Error::<T>::NotExchangeParty);
+
+			ExchangeRates::<T>::remove(&from, &to);
+			ExchangeRatesInto::<T>::remove(&to, &from);
+
+			Self::deposit_event(Event::ExchangeRateWithdrawn { from, to });
+
+			Ok(())
+		}
+
+		/// Set the most points of the brand that can be exchanged in a day, in either direction.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_exchange_cap(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			cap: T::PointBalance,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+			ensure!(BrandTokenById::<T>::contains_key(&brand_id), Error::<T>::BrandTokenNotFound);
+
+			ExchangeCaps::<T>::insert(&brand_id, cap);
+
+			Self::deposit_event(Event::ExchangeCapSet { brand_id, cap });
+
+			Ok(())
+		}
+
+		/// Exchange `amount` of the sender's points in `from` for points of `to` at the rate both
+		/// brands agreed on. The points are taken oldest first and go back to the pool of `from`;
+		/// the new grant comes out of the pool of `to`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(9, 7))]
+		#[transactional]
+		pub fn exchange(
+			origin: OriginFor<T>,
+			from: T::AccountId,
+			to: T::AccountId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(from != to, Error::<T>::SameBrand);
+			ensure!(!amount.is_zero(), Error::<T>::InvalidAmount);
+			pallet_brand_admin::Pallet::<T>::ensure_brand_active(&from)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_active(&to)?;
+
+			let rate = ExchangeRates::<T>::get(&from, &to)
+				.filter(|rate| rate.from_agreed && rate.to_agreed)
+				.ok_or(Error::<T>::ExchangeRateNotAgreed)?
+				.rate;
+			let received: T::PointBalance =
+				rate.saturating_mul_int(amount.saturated_into::<u128>()).saturated_into();
+			ensure!(!received.is_zero(), Error::<T>::InvalidAmount);
+
+			let day = T::Timestamp::now().saturated_into::<u64>() / DAY;
+			Self::record_exchange(&from, amount, day)?;
+			Self::record_exchange(&to, received, day)?;
+
+			Self::spend_points(&from, &sender, amount)?;
+			let mut token = BrandTokenById::<T>::get(&to).ok_or(Error::<T>::BrandTokenNotFound)?;
+			token.amount = token.amount.checked_sub(&received).ok_or(Error::<T>::InsufficentAmount)?;
+			Self::credit(&token, &to, &sender, received, token.default_lifetime)?;
+			BrandTokenById::<T>::insert(&to, token);
+
+			Self::deposit_event(Event::Exchanged { who: sender, from, to, amount, received });
+
+			Ok(())
+		}
+
+		/// Set `amount` of the sender's points in `brand_id` aside, oldest first, for a pending
+		/// purchase. Unless captured, they go back to the sender after `HoldTimeout` blocks.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(5, 6))]
+		#[transactional]
+		pub fn hold(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(!amount.is_zero(), Error::<T>::InvalidAmount);
+			pallet_brand_admin::Pallet::<T>::ensure_not_suspended(&brand_id)?;
+
+			let mut token =
+				BrandTokenById::<T>::get(&brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			let (held, expired) = Self::debit_points(&token, &brand_id, &sender, amount)?;
+			if !expired.is_zero() {
+				token.amount = token.amount.saturating_add(expired);
+				BrandTokenById::<T>::insert(&brand_id, token);
+			}
+
+			let hold_id = NextHoldId::<T>::get();
+			NextHoldId::<T>::put(hold_id.checked_add(1).ok_or(Error::<T>::StorageOverflow)?);
+			let timeout = frame_system::Pallet::<T>::block_number().saturating_add(T::HoldTimeout::get());
+			let hold = Hold {
+				who: sender.clone(),
+				// taken from a bounded list, so it always fits
+				grants: UtxoOf::<T>::try_from(held).unwrap_or_default(),
+				status: HoldStatus::Held,
+				timeout,
+			};
+			Holds::<T>::insert(&brand_id, hold_id, hold);
+			Self::schedule_hold_timeout(&brand_id, hold_id, timeout);
+
+			Self::deposit_event(Event::PointsHeld { brand_id, hold_id, who: sender, amount, timeout });
+
+			Ok(())
+		}
+
+		/// Give held points back to the customer. The brand or its cashiers may do so at any
+		/// time, the customer once the hold timed out.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 4))]
+		pub fn release(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			hold_id: HoldId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let hold = Holds::<T>::get(&brand_id, hold_id).ok_or(Error::<T>::HoldNotFound)?;
+			ensure!(hold.status == HoldStatus::Held, Error::<T>::HoldNotHeld);
+			if sender != hold.who || frame_system::Pallet::<T>::block_number() < hold.timeout {
+				pallet_brand_admin::Pallet::<T>::ensure_brand_role(
+					&brand_id,
+					&sender,
+					BrandRole::Cashier,
+				)?;
+			}
+
+			Self::release_hold(&brand_id, hold_id, hold)
+		}
+
+		/// Take `amount` of the held points into the brand pool and give the rest back to the
+		/// customer. Captured points can be refunded for `RefundPeriod` blocks.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(5, 5))]
+		#[transactional]
+		pub fn capture(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			hold_id: HoldId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Cashier)?;
+			ensure!(!amount.is_zero(), Error::<T>::InvalidAmount);
+			let mut hold = Holds::<T>::get(&brand_id, hold_id).ok_or(Error::<T>::HoldNotFound)?;
+			ensure!(hold.status == HoldStatus::Held, Error::<T>::HoldNotHeld);
+
+			let (captured, rest) = Self::split_grants(hold.grants.into_inner(), amount)?;
+			let released = Self::sum_grants(&rest);
+			let mut token =
+				BrandTokenById::<T>::get(&brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			if !rest.is_empty() {
+				Self::restore_grants(&token, &brand_id, &hold.who, rest)?;
+			}
+			token.amount = token.amount.saturating_add(amount);
+			BrandTokenById::<T>::insert(&brand_id, token);
+
+			HoldTimeouts::<T>::remove(hold.timeout, (&brand_id, hold_id));
+			hold.timeout =
+				frame_system::Pallet::<T>::block_number().saturating_add(T::RefundPeriod::get());
+			// a subset of the held grants, so it always fits
+			hold.grants = UtxoOf::<T>::try_from(captured).unwrap_or_default();
+			hold.status = HoldStatus::Captured;
+			Self::schedule_hold_timeout(&brand_id, hold_id, hold.timeout);
+			Holds::<T>::insert(&brand_id, hold_id, hold);
+
+			Self::deposit_event(Event::HoldCaptured { brand_id, hold_id, captured: amount, released });
+
+			Ok(())
+		}
+
+		/// Give `amount` of the captured points back to the customer out of the brand pool. The
+		/// refunded grants keep their original dates and lifetimes.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(5, 5))]
+		#[transactional]
+		pub fn refund(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			hold_id: HoldId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Cashier)?;
+			ensure!(!amount.is_zero(), Error::<T>::InvalidAmount);
+			let mut hold = Holds::<T>::get(&brand_id, hold_id).ok_or(Error::<T>::HoldNotFound)?;
+			ensure!(hold.status == HoldStatus::Captured, Error::<T>::HoldNotCaptured);
+
+			let (refunded, rest) = Self::split_grants(hold.grants.into_inner(), amount)?;
+			let mut token =
+				BrandTokenById::<T>::get(&brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			token.amount = token.amount.checked_sub(&amount).ok_or(Error::<T>::InsufficentAmount)?;
+			Self::restore_grants(&token, &brand_id, &hold.who, refunded)?;
+			BrandTokenById::<T>::insert(&brand_id, token);
+
+			if rest.is_empty() {
+				HoldTimeouts::<T>::remove(hold.timeout, (&brand_id, hold_id));
+				Holds::<T>::remove(&brand_id, hold_id);
+			} else {
+				// a subset of the captured grants, so it always fits
+				hold.grants = UtxoOf::<T>::try_from(rest).unwrap_or_default();
+				Holds::<T>::insert(&brand_id, hold_id, hold);
+			}
+
+			Self::deposit_event(Event::HoldRefunded { brand_id, hold_id, amount });
+
+			Ok(())
+		}
+
+		/// Let customers pay transaction fees in the brand's points at `rate` points per native
+		/// base unit, the fee itself coming out of the brand's collateral. `None` stops it.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_fee_rate(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			rate: Option<FixedU128>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+			ensure!(BrandTokenById::<T>::contains_key(&brand_id), Error::<T>::BrandTokenNotFound);
+
+			match rate {
+				Some(rate) => FeeRates::<T>::insert(&brand_id, rate),
+				None => FeeRates::<T>::remove(&brand_id),
+			}
+
+			Self::deposit_event(Event::FeeRateSet { brand_id, rate });
+
+			Ok(())
+		}
+
+		/// Set which calls of its customers the brand pays the fees of, the most it pays for one
+		/// customer per day, and whether only customers on its allowlist are covered. The budget
+		/// is kept.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_sponsorship(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			calls: Vec<CallIndex>,
+			daily_limit: BalanceOf<T>,
+			allowlist_only: bool,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+			let calls: BoundedVec<CallIndex, T::MaxSponsoredCalls> =
+				calls.try_into().map_err(|_| Error::<T>::TooManySponsoredCalls)?;
+
+			let budget = Sponsorships::<T>::get(&brand_id).map_or_else(Zero::zero, |s| s.budget);
+			Sponsorships::<T>::insert(
+				&brand_id,
+				Sponsorship { budget, calls, daily_limit, allowlist_only },
+			);
+
+			Self::deposit_event(Event::SponsorshipSet { brand_id });
+
+			Ok(())
+		}
+
+		/// Add `amount` of the brand's native currency to its fee budget, reserving it.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn fund_sponsorship(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+			let mut sponsorship =
+				Sponsorships::<T>::get(&brand_id).ok_or(Error::<T>::SponsorshipNotFound)?;
+			sponsorship.budget =
+				sponsorship.budget.checked_add(&amount).ok_or(Error::<T>::StorageOverflow)?;
+
+			T::Currency::reserve(&brand_id, amount)?;
+			Sponsorships::<T>::insert(&brand_id, sponsorship);
+
+			Self::deposit_event(Event::SponsorshipFunded { brand_id, amount });
+
+			Ok(())
+		}
+
+		/// Take `amount` back out of the brand's fee budget.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn withdraw_sponsorship(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+			let mut sponsorship =
+				Sponsorships::<T>::get(&brand_id).ok_or(Error::<T>::SponsorshipNotFound)?;
+			sponsorship.budget =
+				sponsorship.budget.checked_sub(&amount).ok_or(Error::<T>::InsufficentBalance)?;
+
+			T::Currency::unreserve(&brand_id, amount);
+			Sponsorships::<T>::insert(&brand_id, sponsorship);
+
+			Self::deposit_event(Event::SponsorshipWithdrawn { brand_id, amount });
+
+			Ok(())
+		}
+
+		/// Stop paying fees for customers and release what is left of the budget.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 3))]
+		pub fn end_sponsorship(origin: OriginFor<T>, brand_id: T::AccountId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+			let sponsorship =
+				Sponsorships::<T>::take(&brand_id).ok_or(Error::<T>::SponsorshipNotFound)?;
+
+			T::Currency::unreserve(&brand_id, sponsorship.budget);
+			let _ = SponsorAllowlist::<T>::remove_prefix(&brand_id, None);
+			let _ = SponsoredFees::<T>::remove_prefix(&brand_id, None);
+
+			Self::deposit_event(Event::SponsorshipEnded { brand_id, refunded: sponsorship.budget });
+
+			Ok(())
+		}
+
+		/// Add `who` to, or remove it from, the customers the brand sponsors.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_sponsor_allowlist(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			who: T::AccountId,
+			allowed: bool,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+
+			if allowed {
+				SponsorAllowlist::<T>::insert(&brand_id, &who, true);
+			} else {
+				SponsorAllowlist::<T>::remove(&brand_id, &who);
+			}
+
+			Self::deposit_event(Event::SponsorAllowlistSet { brand_id, who, allowed });
+
+			Ok(())
+		}
+
+		/// Stop `who` from spending, moving or refunding points of the brand.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn freeze(origin: OriginFor<T>, brand_id: T::AccountId, who: T::AccountId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+
+			FrozenAccounts::<T>::insert(&brand_id, &who, true);
+
+			Self::deposit_event(Event::AccountFrozen { brand_id, who });
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn thaw(origin: OriginFor<T>, brand_id: T::AccountId, who: T::AccountId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+
+			FrozenAccounts::<T>::remove(&brand_id, &who);
+
+			Self::deposit_event(Event::AccountThawed { brand_id, who });
+
+			Ok(())
+		}
+
+		/// Set the most points a brand can claw back from one customer, and the blocks a customer
+		/// has to appeal a clawback.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2))]
+		pub fn set_clawback_limits(
+			origin: OriginFor<T>,
+			cap: Option<T::PointBalance>,
+			appeal_window: T::BlockNumber,
+		) -> DispatchResult {
+			T::ClawbackOrigin::ensure_origin(origin)?;
+
+			ClawbackCap::<T>::set(cap);
+			ClawbackAppealWindow::<T>::put(appeal_window);
+
+			Self::deposit_event(Event::ClawbackLimitsSet { cap, appeal_window });
+
+			Ok(())
+		}
+
+		/// Take `amount` of `who`'s points back into the brand pool, newest first, e.g. when they
+		/// were earned through a fraudulent receipt. With an appeal window the points are only
+		/// set aside until the brand executes the clawback.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(6, 4))]
+		pub fn clawback(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			who: T::AccountId,
+			amount: T::PointBalance,
+			reason: Vec<u8>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+			ensure!(!amount.is_zero(), Error::<T>::InvalidAmount);
+			let bounded_reason: BoundedVec<u8, T::MaxReasonLen> =
+				reason.clone().try_into().map_err(|_| Error::<T>::ReasonTooLong)?;
+			let total = ClawedBack::<T>::get(&brand_id, &who)
+				.checked_add(&amount)
+				.ok_or(Error::<T>::StorageOverflow)?;
+			if let Some(cap) = ClawbackCap::<T>::get() {
+				ensure!(total <= cap, Error::<T>::ClawbackCapExceeded);
+			}
+			let mut token =
+				BrandTokenById::<T>::get(&brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			let clawback_id = NextClawbackId::<T>::get();
+			let next_id = clawback_id.checked_add(1).ok_or(Error::<T>::StorageOverflow)?;
+
+			let taken = Self::take_newest(&brand_id, &who, amount)?;
+			ClawedBack::<T>::insert(&brand_id, &who, total);
+
+			let window = ClawbackAppealWindow::<T>::get();
+			if window.is_zero() {
+				token.amount = token.amount.saturating_add(amount);
+				BrandTokenById::<T>::insert(&brand_id, token);
+				Self::deposit_event(Event::ClawedBack { brand_id, who, amount, reason });
+				return Ok(())
+			}
+
+			let execute_at = frame_system::Pallet::<T>::block_number().saturating_add(window);
+			let clawback = PendingClawback {
+				who: who.clone(),
+				// taken from a bounded list, so it always fits
+				grants: UtxoOf::<T>::try_from(taken).unwrap_or_default(),
+				reason: bounded_reason,
+				execute_at,
+				appealed: false,
+			};
+			NextClawbackId::<T>::put(next_id);
+			PendingClawbacks::<T>::insert(&brand_id, clawback_id, clawback);
+
+			Self::deposit_event(Event::ClawbackScheduled {
+				brand_id,
+				clawback_id,
+				who,
+				amount,
+				execute_at,
+			});
+
+			Ok(())
+		}
+
+		/// Appeal a pending clawback against the sender, leaving the decision to
+		/// `ClawbackOrigin`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn appeal_clawback(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			clawback_id: ClawbackId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let mut clawback = PendingClawbacks::<T>::get(&brand_id, clawback_id)
+				.ok_or(Error::<T>::ClawbackNotFound)?;
+			ensure!(clawback.who == sender, Error::<T>::NotClawbackSubject);
+			ensure!(
+				frame_system::Pallet::<T>::block_number() < clawback.execute_at,
+				Error::<T>::AppealWindowClosed
+			);
+			ensure!(!clawback.appealed, Error::<T>::ClawbackAppealed);
+
+			clawback.appealed = true;
+			PendingClawbacks::<T>::insert(&brand_id, clawback_id, clawback);
+
+			Self::deposit_event(Event::ClawbackAppealed { brand_id, clawback_id });
+
+			Ok(())
+		}
+
+		/// Move the points of an unappealed clawback into the brand pool once its appeal window
+		/// passed.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 2))]
+		pub fn execute_clawback(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			clawback_id: ClawbackId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+			let clawback = PendingClawbacks::<T>::get(&brand_id, clawback_id)
+				.ok_or(Error::<T>::ClawbackNotFound)?;
+			ensure!(!clawback.appealed, Error::<T>::ClawbackAppealed);
+			ensure!(
+				frame_system::Pallet::<T>::block_number() >= clawback.execute_at,
+				Error::<T>::AppealWindowOpen
+			);
+
+			Self::settle_clawback(&brand_id, clawback_id, clawback)
+		}
+
+		/// Decide on an appealed clawback: if `upheld`, the points go back to the customer,
+		/// otherwise into the brand pool.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 4))]
+		pub fn resolve_clawback_appeal(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			clawback_id: ClawbackId,
+			upheld: bool,
+		) -> DispatchResult {
+			T::ClawbackOrigin::ensure_origin(origin)?;
+			let clawback = PendingClawbacks::<T>::get(&brand_id, clawback_id)
+				.ok_or(Error::<T>::ClawbackNotFound)?;
+			ensure!(clawback.appealed, Error::<T>::ClawbackNotAppealed);
+
+			if !upheld {
+				return Self::settle_clawback(&brand_id, clawback_id, clawback)
+			}
+
+			let token = BrandTokenById::<T>::get(&brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			let amount = Self::sum_grants(&clawback.grants);
+			Self::restore_grants(&token, &brand_id, &clawback.who, clawback.grants.into_inner())?;
+			ClawedBack::<T>::mutate(&brand_id, &clawback.who, |total| {
+				*total = total.saturating_sub(amount)
+			});
+			PendingClawbacks::<T>::remove(&brand_id, clawback_id);
+
+			Self::deposit_event(Event::ClawbackReversed { brand_id, clawback_id, amount });
+
+			Ok(())
+		}
+
+		/// End a campaign early, returning its remaining budget to the brand pool.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn end_campaign(
+			origin: OriginFor<T>,
+			brand_id: T::AccountId,
+			campaign_id: CampaignId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Manager)?;
+
+			let mut campaigns = Campaigns::<T>::get(&brand_id).into_inner();
+			let index = campaigns
+				.iter()
+				.position(|(id, _)| *id == campaign_id)
+				.ok_or(Error::<T>::CampaignNotFound)?;
+			let (_, campaign) = campaigns.remove(index);
+			let mut token =
+				BrandTokenById::<T>::get(&brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			token.amount = token.amount.saturating_add(campaign.budget);
+
+			// `campaigns` is a subset of a bounded list, so it always fits
+			Campaigns::<T>::insert(&brand_id, CampaignsOf::<T>::try_from(campaigns).unwrap_or_default());
+			BrandTokenById::<T>::insert(&brand_id, token);
+
+			Self::deposit_event(Event::CampaignEnded {
+				brand_id,
+				campaign_id,
+				refunded: campaign.budget,
+			});
+
+			Ok(())
+		}
+
+		/// Give back all points held in a defaulted brand, one that is winding down or suspended,
+		/// for their pro-rata share of the collateral backing the token.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(6, 5))]
+		#[transactional]
+		pub fn claim_refund(origin: OriginFor<T>, brand_id: T::AccountId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(
+				pallet_brand_admin::Pallet::<T>::winding_down(&brand_id).is_some() ||
+					pallet_brand_admin::Pallet::<T>::brand_status(&brand_id) ==
+						BrandStatus::Suspended,
+				Error::<T>::BrandNotWindingDown
+			);
+			ensure!(!FrozenAccounts::<T>::get(&brand_id, &sender), Error::<T>::AccountFrozen);
+
+			// expired grants go back to the pool, only live ones are refunded
+			Self::expire_points(&brand_id, &sender);
+			let amount = Self::cal_sum_balance(&brand_id, &sender);
+			ensure!(!amount.is_zero(), Error::<T>::InsufficentBalance);
+
+			let mut token =
+				BrandTokenById::<T>::get(&brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			let collateral = Self::pro_rata(token.collateral, amount, token.staked);
+			token.staked = token.staked.saturating_sub(amount);
+			token.collateral = token.collateral.saturating_sub(collateral);
+			BrandTokenById::<T>::insert(&brand_id, token);
+			UTXO::<T>::remove(&brand_id, &sender);
+			LastActivity::<T>::remove(&brand_id, &sender);
+
+			let remaining = T::Currency::repatriate_reserved(
+				&brand_id,
+				&sender,
+				collateral,
+				BalanceStatus::Free,
+			)?;
+			ensure!(remaining.is_zero(), Error::<T>::StakeNotMoved);
+
+			Self::deposit_event(Event::Refunded { brand_id, who: sender, amount, collateral });
+
+			Ok(())
+		}
+
+		/// Create the shared token of a coalition. Its pool is kept under the coalition account
+		/// and filled by members through `fund_coalition`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 1))]
+		#[transactional]
+		pub fn create_coalition_token(
+			origin: OriginFor<T>,
+			coalition_id: CoalitionId,
+			symbol: Vec<u8>,
+			decimals: u8,
+			collateral_ratio: FixedU128,
+			default_lifetime: u8,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_coalition_member(coalition_id, &sender)?;
+			ensure!(
+				collateral_ratio >= MinCollateralRatio::<T>::get(),
+				Error::<T>::CollateralRatioTooLow
+			);
+
+			let account = pallet_brand_admin::Pallet::<T>::coalition_account(coalition_id);
+			ensure!(!BrandTokenById::<T>::contains_key(&account), Error::<T>::AlreadyCreatedToken);
+			let symbol: BoundedVec<u8, T::MaxSymbolLen> =
+				symbol.try_into().map_err(|_| Error::<T>::SymbolTooLong)?;
+
+			let new_token = BrandToken {
+				symbol,
+				decimals,
+				amount: Zero::zero(),
+				staked: Zero::zero(),
+				collateral_ratio,
+				collateral: Zero::zero(),
+				default_lifetime,
+				expiry_policy: ExpiryPolicy::Lifetime,
+			};
+			BrandTokenById::<T>::insert(&account, new_token);
+			let asset_id = Self::register_asset(&account)?;
+
+			Self::deposit_event(Event::BrandTokenCreated { brand_id: account, asset_id });
+
+			Ok(())
+		}
+
+		/// Move native currency from a member brand into the coalition account as collateral for
+		/// `amount` new coalition points, at the coalition token's collateral ratio.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+		#[transactional]
+		pub fn fund_coalition(
+			origin: OriginFor<T>,
+			coalition_id: CoalitionId,
+			brand_id: T::AccountId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Minter)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_active(&brand_id)?;
+			pallet_brand_admin::Pallet::<T>::ensure_coalition_member(coalition_id, &brand_id)?;
+
+			let account = pallet_brand_admin::Pallet::<T>::coalition_account(coalition_id);
+			let mut token =
+				BrandTokenById::<T>::get(&account).ok_or(Error::<T>::CoalitionTokenNotFound)?;
+			ensure!(
+				token.collateral_ratio >= MinCollateralRatio::<T>::get(),
+				Error::<T>::CollateralRatioTooLow
+			);
+			let stake = Self::collateral_for(token.collateral_ratio, amount)?;
+			token.staked = token.staked.checked_add(&amount).ok_or(Error::<T>::StorageOverflow)?;
+			token.amount = token.amount.checked_add(&amount).ok_or(Error::<T>::StorageOverflow)?;
+			token.collateral = token.collateral.saturating_add(stake);
+
+			T::Currency::transfer(&brand_id, &account, stake, ExistenceRequirement::KeepAlive)?;
+			T::Currency::reserve(&account, stake)?;
+
+			BrandTokenById::<T>::insert(&account, token);
+
+			Self::deposit_event(Event::CoalitionFunded { coalition_id, brand_id, amount });
+
+			Ok(())
+		}
+
+		/// Issue coalition points to `beneficiary` on behalf of the member `brand_id`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 3))]
+		pub fn earn_coalition(
+			origin: OriginFor<T>,
+			coalition_id: CoalitionId,
+			brand_id: T::AccountId,
+			beneficiary: T::AccountId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(!amount.is_zero(), Error::<T>::InvalidAmount);
+			pallet_brand_admin::Pallet::<T>::ensure_brand_role(&brand_id, &sender, BrandRole::Cashier)?;
+			pallet_brand_admin::Pallet::<T>::ensure_brand_active(&brand_id)?;
+			pallet_brand_admin::Pallet::<T>::ensure_coalition_member(coalition_id, &brand_id)?;
+
+			let account = pallet_brand_admin::Pallet::<T>::coalition_account(coalition_id);
+			let mut token =
+				BrandTokenById::<T>::get(&account).ok_or(Error::<T>::CoalitionTokenNotFound)?;
+			token.amount = token.amount.checked_sub(&amount).ok_or(Error::<T>::InsufficentAmount)?;
+
+			let now = T::Timestamp::now();
+			let grant = Amount { amount, issued_date: now, lifetime: token.default_lifetime };
+			let expires_at = Self::expires_at(&token, &grant, now.saturated_into());
+			let mut utxo = UTXO::<T>::get(&account, &beneficiary).unwrap_or_default();
+			utxo.try_push(grant).map_err(|_| Error::<T>::TooManyUtxoEntries)?;
+			Self::touch(&account, &beneficiary, expires_at);
+
+			BrandTokenById::<T>::insert(&account, token);
+			UTXO::<T>::insert(&account, &beneficiary, utxo);
+			CoalitionLedgers::<T>::mutate(coalition_id, &brand_id, |ledger| {
+				ledger.issued = ledger.issued.saturating_add(amount)
+			});
+
+			Self::deposit_event(Event::CoalitionEarned {
+				coalition_id,
+				brand_id,
+				who: beneficiary,
+				amount,
+			});
+
+			Ok(())
+		}
+
+		/// Spend coalition points at the member `brand_id`. The points go back to the shared
+		/// pool and count as redeemed by that member, which must not be suspended.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(5, 3))]
+		#[transactional]
+		pub fn redeem_coalition(
+			origin: OriginFor<T>,
+			coalition_id: CoalitionId,
+			brand_id: T::AccountId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(!amount.is_zero(), Error::<T>::InvalidAmount);
+			pallet_brand_admin::Pallet::<T>::ensure_not_suspended(&brand_id)?;
+			pallet_brand_admin::Pallet::<T>::ensure_coalition_member(coalition_id, &brand_id)?;
+
+			let account = pallet_brand_admin::Pallet::<T>::coalition_account(coalition_id);
+			Self::spend_points(&account, &sender, amount)?;
+			CoalitionLedgers::<T>::mutate(coalition_id, &brand_id, |ledger| {
+				ledger.redeemed = ledger.redeemed.saturating_add(amount)
+			});
+
+			Self::deposit_event(Event::CoalitionRedeemed {
+				coalition_id,
+				brand_id,
+				who: sender,
+				amount,
+			});
+
+			Ok(())
+		}
+
+		/// Settle the members' positions since the last settlement in native currency, at the
+		/// collateral ratio of the coalition token. Members that issued more than they redeemed
+		/// pay the difference into the coalition account first, then members that redeemed more
+		/// are paid out of it.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(10, 10))]
+		#[transactional]
+		pub fn settle_coalition(origin: OriginFor<T>, coalition_id: CoalitionId) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			pallet_brand_admin::Pallet::<T>::ensure_coalition_member(coalition_id, &sender)?;
+
+			let account = pallet_brand_admin::Pallet::<T>::coalition_account(coalition_id);
+			let ratio = BrandTokenById::<T>::get(&account)
+				.ok_or(Error::<T>::CoalitionTokenNotFound)?
+				.collateral_ratio;
+			let (payers, receivers): (Vec<_>, Vec<_>) =
+				CoalitionLedgers::<T>::drain_prefix(coalition_id)
+					.partition(|(_, ledger)| ledger.issued > ledger.redeemed);
+
+			for (brand_id, ledger) in payers.into_iter().chain(receivers) {
+				Self::settle_ledger(coalition_id, &account, ratio, brand_id, ledger)?;
+			}
+
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Settle one member's `ledger` with the coalition `account`: the member pays for the
+		/// points it issued beyond those it redeemed, or is paid for the excess it redeemed.
+		fn settle_ledger(
+			coalition_id: CoalitionId,
+			account: &T::AccountId,
+			ratio: FixedU128,
+			brand_id: T::AccountId,
+			ledger: CoalitionLedger<T::PointBalance>,
+		) -> DispatchResult {
+			if ledger.issued > ledger.redeemed {
+				let paid = ledger.issued - ledger.redeemed;
+				T::Currency::transfer(
+					&brand_id,
+					account,
+					Self::collateral_for(ratio, paid)?,
+					ExistenceRequirement::KeepAlive,
+				)?;
+				Self::deposit_event(Event::CoalitionSettled {
+					coalition_id,
+					brand_id,
+					paid,
+					received: Zero::zero(),
+				});
+			} else if ledger.redeemed > ledger.issued {
+				let received = ledger.redeemed - ledger.issued;
+				T::Currency::transfer(
+					account,
+					&brand_id,
+					Self::collateral_for(ratio, received)?,
+					ExistenceRequirement::AllowDeath,
+				)?;
+				Self::deposit_event(Event::CoalitionSettled {
+					coalition_id,
+					brand_id,
+					paid: Zero::zero(),
+					received,
+				});
+			}
+			Ok(())
+		}
+
+		/// Collateral required for `points` at `ratio`.
+		pub fn collateral_for(
+			ratio: FixedU128,
+			points: T::PointBalance,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			let raw: u128 = points.try_into().map_err(|_| Error::<T>::StorageOverflow)?;
+			let collateral = ratio.checked_mul_int(raw).ok_or(Error::<T>::StorageOverflow)?;
+			collateral.try_into().map_err(|_| Error::<T>::StorageOverflow.into())
+		}
+
+		/// Share of `collateral` backing `part` out of `whole` points.
+		pub fn pro_rata(
+			collateral: BalanceOf<T>,
+			part: T::PointBalance,
+			whole: T::PointBalance,
+		) -> BalanceOf<T> {
+			if part >= whole {
+				return collateral
+			}
+			let part: u128 = part.saturated_into();
+			let whole: u128 = whole.saturated_into();
+			let share = FixedU128::checked_from_rational(part, whole).unwrap_or_default();
+			share.saturating_mul_int(collateral.saturated_into::<u128>()).saturated_into()
+		}
+
+		/// Live points of `user_id` in the token of `brand_id`; expired grants are not counted.
+		pub fn cal_sum_balance(brand_id: &T::AccountId, user_id: &T::AccountId) -> T::PointBalance {
+			let token = match BrandTokenById::<T>::get(brand_id) {
+				Some(token) => token,
+				None => return Zero::zero(),
+			};
+			let now_u64 = T::Timestamp::now().saturated_into::<u64>();
+			let last_activity = LastActivity::<T>::get(brand_id, user_id);
+			let utxo = UTXO::<T>::get(brand_id, user_id).unwrap_or_default();
+			utxo.iter()
+				.filter(|x| Self::expires_at(&token, x, last_activity).map_or(true, |at| at > now_u64))
+				.fold(Zero::zero(), |sum: T::PointBalance, x| sum.saturating_add(x.amount))
+		}
+
+		/// Moment at which `item` expires under the token's policy, if ever. `last_activity` is
+		/// that of the holder of `item`.
+		pub fn expires_at(token: &BrandTokenOf<T>, item: &AmountOf<T>, last_activity: u64) -> Option<u64> {
+			let lifetime = MONTH.saturating_mul(item.lifetime.into());
+			let issued = item.issued_date.saturated_into::<u64>();
+			match token.expiry_policy {
+				ExpiryPolicy::Lifetime => Some(issued.saturating_add(lifetime)),
+				ExpiryPolicy::FixedDate(at) => Some(at),
+				ExpiryPolicy::Rolling => Some(last_activity.max(issued).saturating_add(lifetime)),
+				ExpiryPolicy::YearEnd => Some(Self::year_end(issued.saturating_add(lifetime))),
+				ExpiryPolicy::Never => None,
+			}
+		}
+
+		/// First moment of the calendar year (UTC) following the one `moment` falls in, with
+		/// `moment` in seconds since the Unix epoch.
+		pub fn year_end(moment: u64) -> u64 {
+			// civil-from-days and days-from-civil, shifted so years start on March 1st
+			let z = moment / DAY + 719468;
+			let era = z / 146097;
+			let doe = z - era * 146097;
+			let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+			let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+			let mp = (5 * doy + 2) / 153;
+			let year = yoe + era * 400 + if mp >= 10 { 1 } else { 0 };
+
+			// January 1st of `year + 1` is day 306 of shifted year `year`
+			let era = year / 400;
+			let yoe = year - era * 400;
+			let doe = yoe * 365 + yoe / 4 - yoe / 100 + 306;
+			(era * 146097 + doe).saturating_sub(719468).saturating_mul(DAY)
+		}
+
+		/// What a brand signs for `voucher`: the voucher tagged with `VOUCHER_CONTEXT` and the
+		/// genesis hash, so it can't be claimed on another chain or by another pallet.
+		pub fn voucher_payload(voucher: &EarnVoucherOf<T>) -> Vec<u8> {
+			let genesis_hash = frame_system::Pallet::<T>::block_hash(T::BlockNumber::zero());
+			(VOUCHER_CONTEXT, genesis_hash, voucher).encode()
+		}
+
+		/// Earliest expiry among `utxo`, if any grant expires at all.
+		fn next_expiry(token: &BrandTokenOf<T>, utxo: &[AmountOf<T>], last_activity: u64) -> Option<u64> {
+			utxo.iter().filter_map(|x| Self::expires_at(token, x, last_activity)).min()
+		}
+
+		/// Record activity of `who` in `brand_id` and queue a sweep for `next_expiry`.
+		fn touch(brand_id: &T::AccountId, who: &T::AccountId, next_expiry: Option<u64>) {
+			LastActivity::<T>::insert(brand_id, who, T::Timestamp::now().saturated_into::<u64>());
+			if let Some(at) = next_expiry {
+				Self::schedule_expiry(brand_id, who, at);
+			}
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Queue `who` to have their points in `brand_id` swept once `expires_at` has passed.
+		pub(crate) fn schedule_expiry(brand_id: &T::AccountId, who: &T::AccountId, expires_at: u64) {
+			let bucket = expires_at / EXPIRY_BUCKET;
+			ExpiryQueue::<T>::insert(bucket, (brand_id, who), ());
+			if NextExpiryBucket::<T>::get().map_or(true, |next| bucket < next) {
+				NextExpiryBucket::<T>::put(bucket);
+			}
+		}
+
+		/// Queue the next expiry of holders from before the expiry queue, continuing from
+		/// `ExpirySeedCursor`, until `MaxExpiriesPerBlock` holders were queued or
+		/// `remaining_weight` runs out.
+		pub(crate) fn seed_expiry_queue(remaining_weight: Weight) -> Weight {
+			let db = T::DbWeight::get();
+			let per_holder = db.reads_writes(4, 2);
+			let mut used = db.reads_writes(1, 1);
+			if used.saturating_add(per_holder) > remaining_weight {
+				return 0
+			}
+
+			let cursor = match ExpirySeedCursor::<T>::get() {
+				Some(cursor) => cursor,
+				None => return db.reads(1),
+			};
+			let mut holders = if cursor.is_empty() {
+				UTXO::<T>::iter()
+			} else {
+				UTXO::<T>::iter_from(cursor.into_inner())
+			};
+			let mut seeded = 0u32;
+			while seeded < T::MaxExpiriesPerBlock::get() &&
+				used.saturating_add(per_holder) <= remaining_weight
+			{
+				let (brand_id, who, utxo) = match holders.next() {
+					Some(holder) => holder,
+					None => {
+						ExpirySeedCursor::<T>::kill();
+						return used
+					},
+				};
+				if let Some(token) = BrandTokenById::<T>::get(&brand_id) {
+					let last_activity = LastActivity::<T>::get(&brand_id, &who);
+					if let Some(next) = Self::next_expiry(&token, &utxo, last_activity) {
+						Self::schedule_expiry(&brand_id, &who, next);
+					}
+				}
+				used = used.saturating_add(per_holder);
+				seeded += 1;
+			}
+
+			let cursor: Result<ExpirySeedCursorOf, _> = holders.last_raw_key().to_vec().try_into();
+			match cursor {
+				Ok(cursor) => ExpirySeedCursor::<T>::put(cursor),
+				// keys always fit, see `integrity_test`
+				Err(_) => ExpirySeedCursor::<T>::kill(),
+			}
+			used
+		}
+
+		/// Queue the next expiry of the holders of a brand whose expiry policy changed, continuing
+		/// from its `ExpiryRequeueCursor`, until `MaxExpiriesPerBlock` holders were queued or
+		/// `remaining_weight` runs out. Earlier queue entries stay, `expire_points` skips them.
+		pub(crate) fn requeue_expiries(remaining_weight: Weight) -> Weight {
+			let db = T::DbWeight::get();
+			let per_holder = db.reads_writes(2, 2);
+			let mut used = db.reads_writes(2, 1);
+			if used.saturating_add(per_holder) > remaining_weight {
+				return 0
+			}
+
+			let (brand_id, cursor) = match ExpiryRequeueCursor::<T>::iter().next() {
+				Some(next) => next,
+				None => return db.reads(1),
+			};
+			let token = match BrandTokenById::<T>::get(&brand_id) {
+				Some(token) => token,
+				None => {
+					ExpiryRequeueCursor::<T>::remove(&brand_id);
+					return used
+				},
+			};
+			// the holders of a brand are next to each other, right after its prefix
+			let mut holders = UTXO::<T>::iter_from(cursor.into_inner());
+			let mut queued = 0u32;
+			while queued < T::MaxExpiriesPerBlock::get() &&
+				used.saturating_add(per_holder) <= remaining_weight
+			{
+				match holders.next() {
+					Some((brand, who, utxo)) if brand == brand_id => {
+						let last_activity = LastActivity::<T>::get(&brand_id, &who);
+						if let Some(next) = Self::next_expiry(&token, &utxo, last_activity) {
+							Self::schedule_expiry(&brand_id, &who, next);
+						}
+					},
+					_ => {
+						ExpiryRequeueCursor::<T>::remove(&brand_id);
+						return used
+					},
+				}
+				used = used.saturating_add(per_holder);
+				queued += 1;
+			}
+
+			let cursor: Result<ExpirySeedCursorOf, _> = holders.last_raw_key().to_vec().try_into();
+			match cursor {
+				Ok(cursor) => ExpiryRequeueCursor::<T>::insert(&brand_id, cursor),
+				// keys always fit, see `integrity_test`
+				Err(_) => ExpiryRequeueCursor::<T>::remove(&brand_id),
+			}
+			used
+		}
+
+		/// Sweep queued holders from buckets that have fully passed, oldest first, until
+		/// `MaxExpiriesPerBlock` holders were handled or `remaining_weight` runs out.
+		pub(crate) fn process_expiry_queue(remaining_weight: Weight) -> Weight {
+			let db = T::DbWeight::get();
+			let per_holder = db.reads_writes(4, 4);
+			let mut used = db.reads(2);
+			if used.saturating_add(per_holder) > remaining_weight {
+				return 0
+			}
+
+			let mut bucket = match NextExpiryBucket::<T>::get() {
+				Some(bucket) => bucket,
+				None => return db.reads(1),
+			};
+			let current = T::Timestamp::now().saturated_into::<u64>() / EXPIRY_BUCKET;
+			let start = bucket;
+			let mut swept = 0u32;
+			while bucket < current &&
+				swept < T::MaxExpiriesPerBlock::get() &&
+				used.saturating_add(per_holder) <= remaining_weight
+			{
+				used = used.saturating_add(db.reads(1));
+				match ExpiryQueue::<T>::iter_key_prefix(bucket).next() {
+					Some((brand_id, who)) => {
+						ExpiryQueue::<T>::remove(bucket, (&brand_id, &who));
+						Self::expire_points(&brand_id, &who);
+						used = used.saturating_add(per_holder);
+						swept += 1;
+					},
+					None => bucket += 1,
+				}
+			}
+
+			if bucket != start {
+				NextExpiryBucket::<T>::put(bucket);
+				used = used.saturating_add(db.writes(1));
+			}
+			used
+		}
+
+		/// Return the expired points of `who` in `brand_id` to the pool and queue the next expiry
+		/// among the grants left.
+		fn expire_points(brand_id: &T::AccountId, who: &T::AccountId) {
+			let mut token = match BrandTokenById::<T>::get(brand_id) {
+				Some(token) => token,
+				None => return,
+			};
+			let utxo = match UTXO::<T>::get(brand_id, who) {
+				Some(utxo) => utxo.into_inner(),
+				None => return,
+			};
+			let now_u64 = T::Timestamp::now().saturated_into::<u64>();
+			let last_activity = LastActivity::<T>::get(brand_id, who);
+
+			let (expired, live): (Vec<_>, Vec<_>) = utxo.into_iter().partition(|x| {
+				Self::expires_at(&token, x, last_activity).map_or(false, |at| at <= now_u64)
+			});
+			if let Some(next) = Self::next_expiry(&token, &live, last_activity) {
+				Self::schedule_expiry(brand_id, who, next);
+			}
+			if expired.is_empty() {
+				return
+			}
+
+			let amount = expired
+				.iter()
+				.fold(T::PointBalance::zero(), |sum, x| sum.saturating_add(x.amount));
+			token.amount = token.amount.saturating_add(amount);
+			BrandTokenById::<T>::insert(brand_id, token);
+			if live.is_empty() {
+				UTXO::<T>::remove(brand_id, who);
+				LastActivity::<T>::remove(brand_id, who);
+			} else {
+				// `live` is a subset of a bounded list, so it always fits
+				UTXO::<T>::insert(brand_id, who, UtxoOf::<T>::try_from(live).unwrap_or_default());
+			}
+
+			Self::deposit_event(Event::PointsExpired {
+				brand: brand_id.clone(),
+				who: who.clone(),
+				amount,
+			});
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Move `amount` points from the pool of `brand_id` to `beneficiary` as a new grant, along
+		/// with the bonus of any running campaign and, on a referred customer's first earn, the
+		/// referral bonus.
+		#[transactional]
+		pub(crate) fn issue_points(
+			brand_id: &T::AccountId,
+			beneficiary: &T::AccountId,
+			amount: T::PointBalance,
+			outlet: Option<OutletId>,
+			lifetime: Option<u8>,
+		) -> DispatchResult {
+			ensure!(!amount.is_zero(), Error::<T>::InvalidAmount);
+			let mut brand_token =
+				BrandTokenById::<T>::get(brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			brand_token.amount =
+				brand_token.amount.checked_sub(&amount).ok_or(Error::<T>::InsufficentAmount)?;
+
+			let issued_date = T::Timestamp::now();
+			let bonus = Self::apply_campaigns(&mut brand_token, brand_id, beneficiary, amount)
+				.saturating_add(Self::reward_referral(&mut brand_token, brand_id, beneficiary));
+			let lifetime = lifetime.unwrap_or(brand_token.default_lifetime);
+			Self::credit(&brand_token, brand_id, beneficiary, amount.saturating_add(bonus), lifetime)?;
+			HasEarned::<T>::insert(brand_id, beneficiary, true);
+
+			BrandTokenById::<T>::insert(brand_id, brand_token);
+
+			Self::deposit_event(Event::Earned { amount, bonus, issued_date, outlet });
+
+			Ok(())
+		}
+
+		/// Points of `brand_id` a fee of `fee` native base units costs, if the brand takes points
+		/// for fees.
+		pub fn fee_in_points(brand_id: &T::AccountId, fee: BalanceOf<T>) -> Option<T::PointBalance> {
+			let rate = FeeRates::<T>::get(brand_id)?;
+			rate.checked_mul_int(fee.saturated_into::<u128>())?.try_into().ok()
+		}
+
+		/// Whether `who` can pay a fee of `fee` with `points` points of `brand_id` and the brand's
+		/// collateral can cover the fee.
+		pub fn can_pay_fee(
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			fee: BalanceOf<T>,
+			points: T::PointBalance,
+		) -> bool {
+			let covered =
+				BrandTokenById::<T>::get(brand_id).map_or(false, |token| token.collateral >= fee);
+			covered &&
+				pallet_brand_admin::Pallet::<T>::ensure_not_suspended(brand_id).is_ok() &&
+				!FrozenAccounts::<T>::get(brand_id, who) &&
+				Self::cal_sum_balance(brand_id, who) >= points
+		}
+
+		/// Take `points` of `who`'s points oldest first into the pool of `brand_id` ahead of a fee
+		/// of at most `fee`, which `settle_fee` then pays. Returns the taken grants.
+		pub fn withdraw_fee(
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			fee: BalanceOf<T>,
+			points: T::PointBalance,
+		) -> Result<Vec<AmountOf<T>>, DispatchError> {
+			ensure!(Self::can_pay_fee(brand_id, who, fee, points), Error::<T>::InsufficentBalance);
+			let mut token =
+				BrandTokenById::<T>::get(brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			let (grants, expired) = Self::debit_points(&token, brand_id, who, points)?;
+			token.amount = token.amount.saturating_add(points).saturating_add(expired);
+			BrandTokenById::<T>::insert(brand_id, token);
+
+			Ok(grants)
+		}
+
+		/// Pay the actual `fee` of a transaction out of the collateral of `brand_id`, giving the
+		/// newest `refund` points of the `grants` taken for it back to `who` with their original
+		/// dates. The staked points shrink along with the collateral.
+		///
+		/// Runs after the transaction, so it never fails: a refund that doesn't fit among the
+		/// customer's grants stays in the pool, and a fee beyond the collateral is only paid up to
+		/// the collateral.
+		pub fn settle_fee(
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			fee: BalanceOf<T>,
+			grants: Vec<AmountOf<T>>,
+			refund: T::PointBalance,
+		) {
+			let points = Self::sum_grants(&grants);
+			let mut token = match BrandTokenById::<T>::get(brand_id) {
+				Some(token) => token,
+				None => {
+					// the token and its collateral went away during the transaction
+					let _ = T::Currency::slash(brand_id, fee);
+					return
+				},
+			};
+
+			let mut refund = refund.min(points).min(token.amount);
+			if !refund.is_zero() {
+				let refunded = Self::split_grants(grants, points - refund)
+					.map(|(_, refunded)| refunded)
+					.unwrap_or_default();
+				if Self::restore_grants(&token, brand_id, who, refunded).is_ok() {
+					token.amount -= refund;
+				} else {
+					refund = Zero::zero();
+				}
+			}
+			let fee = fee.min(token.collateral);
+			// the points the fee's share of the collateral backed are no longer staked, so refunds
+			// keep paying out at the same ratio
+			let unbacked = FixedU128::checked_from_rational(
+				fee.saturated_into::<u128>(),
+				token.collateral.saturated_into::<u128>(),
+			)
+			.unwrap_or_default()
+			.saturating_mul_int(token.staked.saturated_into::<u128>());
+			token.staked = token.staked.saturating_sub(unbacked.saturated_into());
+			token.collateral -= fee;
+			// the fee is burned, as native fees are
+			let _ = T::Currency::slash_reserved(brand_id, fee);
+			BrandTokenById::<T>::insert(brand_id, token);
+
+			Self::deposit_event(Event::FeePaidInPoints {
+				brand_id: brand_id.clone(),
+				who: who.clone(),
+				points: points - refund,
+				fee,
+			});
+		}
+
+		/// Fees `brand_id` already paid for `who` today.
+		fn sponsored_today(brand_id: &T::AccountId, who: &T::AccountId, day: u64) -> BalanceOf<T> {
+			match SponsoredFees::<T>::get(brand_id, who) {
+				(last_day, paid) if last_day == day => paid,
+				_ => Zero::zero(),
+			}
+		}
+
+		/// Whether `brand_id` pays a fee of `fee` for `who` calling `call`.
+		pub fn can_sponsor(
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			call: CallIndex,
+			fee: BalanceOf<T>,
+		) -> bool {
+			let sponsorship = match Sponsorships::<T>::get(brand_id) {
+				Some(sponsorship) => sponsorship,
+				None => return false,
+			};
+			let day = T::Timestamp::now().saturated_into::<u64>() / DAY;
+			let paid = Self::sponsored_today(brand_id, who, day);
+			sponsorship.calls.contains(&call) &&
+				(!sponsorship.allowlist_only || SponsorAllowlist::<T>::get(brand_id, who)) &&
+				sponsorship.budget >= fee &&
+				paid.saturating_add(fee) <= sponsorship.daily_limit &&
+				pallet_brand_admin::Pallet::<T>::ensure_not_suspended(brand_id).is_ok()
+		}
+
+		/// Set `fee` aside from the budget of `brand_id` for a transaction of `who`, which
+		/// `settle_sponsored_fee` then pays.
+		pub fn withdraw_sponsored_fee(
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			call: CallIndex,
+			fee: BalanceOf<T>,
+		) -> DispatchResult {
+			ensure!(Self::can_sponsor(brand_id, who, call, fee), Error::<T>::SponsorshipExhausted);
+			let mut sponsorship =
+				Sponsorships::<T>::get(brand_id).ok_or(Error::<T>::SponsorshipNotFound)?;
+			sponsorship.budget = sponsorship.budget.saturating_sub(fee);
+			Sponsorships::<T>::insert(brand_id, sponsorship);
+
+			let day = T::Timestamp::now().saturated_into::<u64>() / DAY;
+			let paid = Self::sponsored_today(brand_id, who, day).saturating_add(fee);
+			SponsoredFees::<T>::insert(brand_id, who, (day, paid));
+
+			Ok(())
+		}
+
+		/// Pay the actual `fee` of a transaction of `who` out of the reserve of `brand_id`,
+		/// giving what was set aside beyond it back to the budget and the customer's daily limit.
+		/// Runs after the transaction, so it never fails.
+		pub fn settle_sponsored_fee(
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			withdrawn: BalanceOf<T>,
+			fee: BalanceOf<T>,
+		) {
+			let refund = withdrawn.saturating_sub(fee);
+			if !refund.is_zero() {
+				let kept = Sponsorships::<T>::mutate(brand_id, |sponsorship| match sponsorship {
+					Some(sponsorship) => {
+						sponsorship.budget = sponsorship.budget.saturating_add(refund);
+						true
+					},
+					None => false,
+				});
+				// the sponsorship ended while the transaction ran, and its budget was released
+				if !kept {
+					T::Currency::unreserve(brand_id, refund);
+				}
+				SponsoredFees::<T>::mutate(brand_id, who, |(_, paid)| {
+					*paid = paid.saturating_sub(refund)
+				});
+			}
+			// the fee is burned, as native fees are
+			let _ = T::Currency::slash_reserved(brand_id, fee);
+
+			Self::deposit_event(Event::FeeSponsored {
+				brand_id: brand_id.clone(),
+				who: who.clone(),
+				fee,
+			});
+		}
+
+		/// Collect `items`, counting each against `budget` (see `BrandLifecycle`).
+		fn take_items<I: Iterator>(
+			items: I,
+			budget: &mut u32,
+		) -> Result<Vec<I::Item>, DispatchError> {
+			pallet_brand_admin::Pallet::<T>::take_items(items, budget)
+		}
+
+		/// Give the token of `brand_id` the next asset id.
+		fn register_asset(brand_id: &T::AccountId) -> Result<BrandAssetId, DispatchError> {
+			let asset_id = NextAssetId::<T>::get();
+			NextAssetId::<T>::put(asset_id.checked_add(1).ok_or(Error::<T>::StorageOverflow)?);
+			AssetIds::<T>::insert(brand_id, asset_id);
+			AssetBrands::<T>::insert(asset_id, brand_id);
+
+			Ok(asset_id)
+		}
+
+		/// Back `amount` new points of `brand_id` with collateral and give them to `who`, or put
+		/// them in the pool when `who` is the brand. Fails unless the brand is active.
+		#[transactional]
+		pub(crate) fn do_mint(
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			pallet_brand_admin::Pallet::<T>::ensure_brand_active(brand_id)?;
+			let mut token =
+				BrandTokenById::<T>::get(brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			ensure!(
+				token.collateral_ratio >= MinCollateralRatio::<T>::get(),
+				Error::<T>::CollateralRatioTooLow
+			);
+			let collateral = Self::collateral_for(token.collateral_ratio, amount)?;
+			token.staked = token.staked.checked_add(&amount).ok_or(Error::<T>::StorageOverflow)?;
+			token.collateral = token.collateral.saturating_add(collateral);
+			T::Currency::reserve(brand_id, collateral)?;
+
+			if who == brand_id {
+				token.amount = token.amount.checked_add(&amount).ok_or(Error::<T>::StorageOverflow)?;
+			} else {
+				Self::credit(&token, brand_id, who, amount, token.default_lifetime)?;
+			}
+			BrandTokenById::<T>::insert(brand_id, token);
+
+			Ok(())
+		}
+
+		/// Burn `amount` of `who`'s points of `brand_id` oldest first, or of the pool when `who` is
+		/// the brand, releasing the collateral backing them.
+		#[transactional]
+		pub(crate) fn do_burn(
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			let mut token =
+				BrandTokenById::<T>::get(brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			if who == brand_id {
+				token.amount =
+					token.amount.checked_sub(&amount).ok_or(Error::<T>::InsufficentAmount)?;
+			} else {
+				let (_, expired) = Self::debit_points(&token, brand_id, who, amount)?;
+				token.amount = token.amount.saturating_add(expired);
+			}
+			let collateral = Self::pro_rata(token.collateral, amount, token.staked);
+			token.staked = token.staked.checked_sub(&amount).ok_or(Error::<T>::InsufficentAmount)?;
+			token.collateral = token.collateral.saturating_sub(collateral);
+			T::Currency::unreserve(brand_id, collateral);
+			BrandTokenById::<T>::insert(brand_id, token);
+
+			Ok(())
+		}
+
+		/// Move `amount` points of `brand_id` from `source` to `dest`. Points leaving the pool
+		/// become a new grant and points reaching it are spent. Between customers the grants keep
+		/// their dates, and the brand must allow transfers. Nothing moves while the brand is
+		/// suspended.
+		pub(crate) fn do_transfer_points(
+			brand_id: &T::AccountId,
+			source: &T::AccountId,
+			dest: &T::AccountId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			if source == dest || amount.is_zero() {
+				return Ok(())
+			}
+			pallet_brand_admin::Pallet::<T>::ensure_not_suspended(brand_id)?;
+			if source == brand_id {
+				let mut token =
+					BrandTokenById::<T>::get(brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+				token.amount =
+					token.amount.checked_sub(&amount).ok_or(Error::<T>::InsufficentAmount)?;
+				Self::credit(&token, brand_id, dest, amount, token.default_lifetime)?;
+				BrandTokenById::<T>::insert(brand_id, token);
+				Ok(())
+			} else if dest == brand_id {
+				Self::spend_points(brand_id, source, amount)
+			} else {
+				ensure!(P2PTransferAllowed::<T>::get(brand_id), Error::<T>::P2PTransferDisabled);
+				Self::move_points(brand_id, source, dest, amount)
+			}
+		}
+
+		/// Take `amount` of `who`'s points in `brand_id` newest first, expired or not, returning
+		/// the taken grants with their original dates.
+		fn take_newest(
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			amount: T::PointBalance,
+		) -> Result<Vec<AmountOf<T>>, DispatchError> {
+			let mut utxo = UTXO::<T>::get(brand_id, who).unwrap_or_default().into_inner();
+			let mut taken = Vec::new();
+			let mut left = amount;
+			for item in utxo.iter_mut().rev() {
+				if left.is_zero() {
+					break
+				}
+				let part = item.amount.min(left);
+				taken.push(Amount { amount: part, ..item.clone() });
+				item.amount -= part;
+				left -= part;
+			}
+			ensure!(left.is_zero(), Error::<T>::InsufficentBalance);
+
+			utxo.retain(|x| !x.amount.is_zero());
+			if utxo.is_empty() {
+				UTXO::<T>::remove(brand_id, who);
+			} else {
+				// a subset of a bounded list, so it always fits
+				UTXO::<T>::insert(brand_id, who, UtxoOf::<T>::try_from(utxo).unwrap_or_default());
+			}
+
+			Ok(taken)
+		}
+
+		/// Move the points of a pending clawback into the brand pool and drop it.
+		fn settle_clawback(
+			brand_id: &T::AccountId,
+			clawback_id: ClawbackId,
+			clawback: PendingClawbackOf<T>,
+		) -> DispatchResult {
+			let mut token =
+				BrandTokenById::<T>::get(brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			let amount = Self::sum_grants(&clawback.grants);
+			token.amount = token.amount.saturating_add(amount);
+			BrandTokenById::<T>::insert(brand_id, token);
+			PendingClawbacks::<T>::remove(brand_id, clawback_id);
+
+			Self::deposit_event(Event::ClawedBack {
+				brand_id: brand_id.clone(),
+				who: clawback.who,
+				amount,
+				reason: clawback.reason.into_inner(),
+			});
+
+			Ok(())
+		}
+
+		/// Split `grants` into `amount` points taken oldest first and the rest.
+		fn split_grants(
+			grants: Vec<AmountOf<T>>,
+			amount: T::PointBalance,
+		) -> Result<(Vec<AmountOf<T>>, Vec<AmountOf<T>>), DispatchError> {
+			let mut taken = Vec::new();
+			let mut rest = Vec::new();
+			let mut left = amount;
+			for mut item in grants {
+				let part = item.amount.min(left);
+				if !part.is_zero() {
+					taken.push(Amount { amount: part, ..item.clone() });
+					item.amount -= part;
+					left -= part;
+				}
+				if !item.amount.is_zero() {
+					rest.push(item);
+				}
+			}
+			ensure!(left.is_zero(), Error::<T>::HoldAmountTooHigh);
+
+			Ok((taken, rest))
+		}
+
+		fn sum_grants(grants: &[AmountOf<T>]) -> T::PointBalance {
+			grants.iter().fold(Zero::zero(), |sum: T::PointBalance, x| sum.saturating_add(x.amount))
+		}
+
+		/// Put `grants` back among `who`'s points in `brand_id`, keeping their original dates.
+		fn restore_grants(
+			token: &BrandTokenOf<T>,
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			grants: Vec<AmountOf<T>>,
+		) -> DispatchResult {
+			let utxo = Self::merge_grants(brand_id, who, grants)?;
+			let now_u64 = T::Timestamp::now().saturated_into::<u64>();
+			Self::touch(brand_id, who, Self::next_expiry(token, &utxo, now_u64));
+			UTXO::<T>::insert(brand_id, who, utxo);
+
+			Ok(())
+		}
+
+		/// `who`'s points in `brand_id` with `grants` added, oldest first.
+		fn merge_grants(
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			grants: Vec<AmountOf<T>>,
+		) -> Result<UtxoOf<T>, DispatchError> {
+			let mut utxo = UTXO::<T>::get(brand_id, who).unwrap_or_default().into_inner();
+			utxo.extend(grants);
+			utxo.sort_by_key(|item| item.issued_date.saturated_into::<u64>());
+			utxo.try_into().map_err(|_| Error::<T>::TooManyUtxoEntries.into())
+		}
+
+		/// Give the points of a held hold back to its customer and drop it.
+		fn release_hold(brand_id: &T::AccountId, hold_id: HoldId, hold: HoldOf<T>) -> DispatchResult {
+			let token = BrandTokenById::<T>::get(brand_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			let amount = Self::sum_grants(&hold.grants);
+			Self::restore_grants(&token, brand_id, &hold.who, hold.grants.into_inner())?;
+			HoldTimeouts::<T>::remove(hold.timeout, (brand_id, hold_id));
+			Holds::<T>::remove(brand_id, hold_id);
+
+			Self::deposit_event(Event::HoldReleased { brand_id: brand_id.clone(), hold_id, amount });
+
+			Ok(())
+		}
+
+		fn schedule_hold_timeout(brand_id: &T::AccountId, hold_id: HoldId, at: T::BlockNumber) {
+			HoldTimeouts::<T>::insert(at, (brand_id, hold_id), ());
+			if NextHoldTimeout::<T>::get().map_or(true, |next| at < next) {
+				NextHoldTimeout::<T>::put(at);
+			}
+		}
+
+		/// Release held holds and close captured ones whose timeout block was reached, oldest
+		/// first, until `MaxHoldTimeoutsPerBlock` holds were handled or `remaining_weight` runs
+		/// out. A hold whose points don't fit back among the customer's grants stays until the
+		/// customer releases it.
+		pub(crate) fn process_hold_timeouts(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			let db = T::DbWeight::get();
+			let per_hold = db.reads_writes(4, 5);
+			let mut used = db.reads(1);
+			if used.saturating_add(per_hold) > remaining_weight {
+				return 0
+			}
+
+			let mut block = match NextHoldTimeout::<T>::get() {
+				Some(block) => block,
+				None => return used,
+			};
+			let start = block;
+			let mut handled = 0u32;
+			while block <= now &&
+				handled < T::MaxHoldTimeoutsPerBlock::get() &&
+				used.saturating_add(per_hold) <= remaining_weight
+			{
+				used = used.saturating_add(db.reads(1));
+				match HoldTimeouts::<T>::iter_key_prefix(block).next() {
+					Some((brand_id, hold_id)) => {
+						HoldTimeouts::<T>::remove(block, (&brand_id, hold_id));
+						match Holds::<T>::get(&brand_id, hold_id) {
+							Some(hold) if hold.status == HoldStatus::Held => {
+								let _ = Self::release_hold(&brand_id, hold_id, hold);
+							},
+							Some(_) => {
+								Holds::<T>::remove(&brand_id, hold_id);
+								Self::deposit_event(Event::HoldClosed { brand_id, hold_id });
+							},
+							None => {},
+						}
+						used = used.saturating_add(per_hold);
+						handled += 1;
+					},
+					None => block = block.saturating_add(One::one()),
+				}
+			}
+
+			if block != start {
+				NextHoldTimeout::<T>::put(block);
+				used = used.saturating_add(db.writes(1));
+			}
+			used
+		}
+
+		/// Add `amount` to the exchange volume of `brand_id` on `day`, failing above its cap.
+		fn record_exchange(
+			brand_id: &T::AccountId,
+			amount: T::PointBalance,
+			day: u64,
+		) -> DispatchResult {
+			let (last_day, volume) = ExchangeVolume::<T>::get(brand_id);
+			let volume = if last_day == day { volume } else { Zero::zero() };
+			let volume = volume.checked_add(&amount).ok_or(Error::<T>::StorageOverflow)?;
+			ensure!(volume <= ExchangeCaps::<T>::get(brand_id), Error::<T>::ExchangeCapExceeded);
+			ExchangeVolume::<T>::insert(brand_id, (day, volume));
+
+			Ok(())
+		}
+
+		/// Give `who` a new grant of `amount` points, already taken out of the pool of `token`.
+		fn credit(
+			token: &BrandTokenOf<T>,
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			amount: T::PointBalance,
+			lifetime: u8,
+		) -> DispatchResult {
+			let issued_date = T::Timestamp::now();
+			let grant = Amount { amount, issued_date, lifetime };
+			let expires_at = Self::expires_at(token, &grant, issued_date.saturated_into());
+			let mut utxo = UTXO::<T>::get(brand_id, who).unwrap_or_default();
+			utxo.try_push(grant).map_err(|_| Error::<T>::TooManyUtxoEntries)?;
+			Self::touch(brand_id, who, expires_at);
+			UTXO::<T>::insert(brand_id, who, utxo);
+
+			Ok(())
+		}
+
+		/// On the first earn of a referred `referee`, credit its referrer with the referral bonus
+		/// and return the referee's share. Nothing is paid when the pool can't cover both shares
+		/// or the referrer has no room for another grant, and the bonus is not retried later.
+		fn reward_referral(
+			token: &mut BrandTokenOf<T>,
+			brand_id: &T::AccountId,
+			referee: &T::AccountId,
+		) -> T::PointBalance {
+			let mut referral = match Referrals::<T>::get(brand_id, referee) {
+				Some(referral) if referral.reward.is_none() => referral,
+				_ => return Zero::zero(),
+			};
+
+			let bonus = ReferralBonus::<T>::get(brand_id);
+			let has_room = UTXO::<T>::decode_len(brand_id, &referral.referrer)
+				.map_or(true, |len| len < T::MaxUtxoEntries::get() as usize);
+			let rest = token.amount.checked_sub(&bonus.saturating_add(bonus));
+			let paid = match rest {
+				Some(rest) if has_room && !bonus.is_zero() => {
+					token.amount = rest;
+					// the referrer has room, so this can't fail
+					let _ = Self::credit(
+						token,
+						brand_id,
+						&referral.referrer,
+						bonus,
+						token.default_lifetime,
+					);
+					ReferralCounts::<T>::mutate(brand_id, &referral.referrer, |count| {
+						*count = count.saturating_add(1)
+					});
+					Self::deposit_event(Event::ReferralRewarded {
+						brand_id: brand_id.clone(),
+						referee: referee.clone(),
+						referrer: referral.referrer.clone(),
+						bonus,
+					});
+					bonus
+				},
+				_ => Zero::zero(),
+			};
+
+			referral.reward = Some(paid);
+			Referrals::<T>::insert(brand_id, referee, referral);
+
+			paid
+		}
+
+		/// Bonus points the running campaigns of `brand_id` give `who` for earning `base`, taken
+		/// from their budgets. Ended campaigns return their budget to `token`.
+		fn apply_campaigns(
+			token: &mut BrandTokenOf<T>,
+			brand_id: &T::AccountId,
+			who: &T::AccountId,
+			base: T::PointBalance,
+		) -> T::PointBalance {
+			let mut campaigns = Campaigns::<T>::get(brand_id).into_inner();
+			if campaigns.is_empty() {
+				return Zero::zero()
+			}
+			let now = T::Timestamp::now().saturated_into::<u64>();
+			Self::prune_campaigns(token, &mut campaigns, now);
+
+			let mut total = T::PointBalance::zero();
+			for (campaign_id, campaign) in campaigns.iter_mut() {
+				if campaign.start > now {
+					continue
+				}
+				let multiplied: T::PointBalance = campaign
+					.multiplier
+					.saturating_mul_int(base.saturated_into::<u128>())
+					.saturated_into();
+				let granted = CampaignBonuses::<T>::get(*campaign_id, who);
+				let bonus = multiplied
+					.saturating_sub(base)
+					.saturating_add(campaign.bonus)
+					.min(campaign.customer_cap.saturating_sub(granted))
+					.min(campaign.budget);
+				if bonus.is_zero() {
+					continue
+				}
+
+				campaign.budget -= bonus;
+				CampaignBonuses::<T>::insert(*campaign_id, who, granted.saturating_add(bonus));
+				total = total.saturating_add(bonus);
+
+				Self::deposit_event(Event::CampaignBonusGranted {
+					brand_id: brand_id.clone(),
+					campaign_id: *campaign_id,
+					who: who.clone(),
+					base,
+					bonus,
+				});
+			}
+			// `campaigns` is a subset of a bounded list, so it always fits
+			Campaigns::<T>::insert(brand_id, CampaignsOf::<T>::try_from(campaigns).unwrap_or_default());
+
+			total
+		}
+
+		/// Drop the campaigns that ended by `now`, returning their budget to `token`.
+		fn prune_campaigns(
+			token: &mut BrandTokenOf<T>,
+			campaigns: &mut Vec<(CampaignId, CampaignOf<T>)>,
+			now: u64,
+		) {
+			campaigns.retain(|(_, campaign)| {
+				if campaign.end > now {
+					return true
+				}
+				token.amount = token.amount.saturating_add(campaign.budget);
+				false
+			});
+		}
+
+		/// Spend `amount` of `who`'s points in the token of `token_id` oldest first, returning
+		/// them to the pool. Expired entries met on the way go back to the pool as well.
+		pub(crate) fn spend_points(
+			token_id: &T::AccountId,
+			who: &T::AccountId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			let mut token =
+				BrandTokenById::<T>::get(token_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			let (_, expired) = Self::debit_points(&token, token_id, who, amount)?;
+
+			// return spent and expired utxo to the pool
+			token.amount = token.amount.saturating_add(amount).saturating_add(expired);
+			BrandTokenById::<T>::insert(token_id, token);
+
+			Ok(())
+		}
+
+		/// Move `amount` of `from`'s points in the token of `token_id` to `to`, oldest first. The
+		/// moved grants keep their `issued_date` so a transfer never extends their lifetime.
+		pub(crate) fn move_points(
+			token_id: &T::AccountId,
+			from: &T::AccountId,
+			to: &T::AccountId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			let mut token =
+				BrandTokenById::<T>::get(token_id).ok_or(Error::<T>::BrandTokenNotFound)?;
+			let (moved, expired) = Self::debit_points(&token, token_id, from, amount)?;
+			// receiving points is no activity of `to`, so it doesn't push back when they expire
+			let utxo = Self::merge_grants(token_id, to, moved)?;
+			let last_activity = LastActivity::<T>::get(token_id, to);
+			if let Some(next) = Self::next_expiry(&token, &utxo, last_activity) {
+				Self::schedule_expiry(token_id, to, next);
+			}
+			UTXO::<T>::insert(token_id, to, utxo);
+
+			if !expired.is_zero() {
+				token.amount = token.amount.saturating_add(expired);
+				BrandTokenById::<T>::insert(token_id, token);
+			}
+
+			Ok(())
+		}
+
+		/// Take `amount` of `who`'s live points oldest first and drop expired grants met on the
+		/// way. Returns the taken grants with their original dates and the expired amount.
+		fn debit_points(
+			token: &BrandTokenOf<T>,
+			token_id: &T::AccountId,
+			who: &T::AccountId,
+			amount: T::PointBalance,
+		) -> Result<(Vec<AmountOf<T>>, T::PointBalance), DispatchError> {
+			ensure!(!FrozenAccounts::<T>::get(token_id, who), Error::<T>::AccountFrozen);
+			let mut utxo = UTXO::<T>::get(token_id, who).unwrap_or_default().into_inner();
+			let now_u64 = T::Timestamp::now().saturated_into::<u64>();
+			let last_activity = LastActivity::<T>::get(token_id, who);
+
+			let mut taken = Vec::new();
+			let mut expired = T::PointBalance::zero();
+			let mut tmp_amount = amount;
+			for item in utxo.iter_mut() {
+				if Self::expires_at(token, item, last_activity).map_or(false, |at| at <= now_u64) {
+					expired = expired.saturating_add(item.amount);
+					item.amount = Zero::zero();
+					continue
+				}
+				let spent = item.amount.min(tmp_amount);
+				if !spent.is_zero() {
+					taken.push(Amount {
+						amount: spent,
+						issued_date: item.issued_date,
+						lifetime: item.lifetime,
+					});
+				}
+				item.amount -= spent;
+				tmp_amount -= spent;
+			}
+			ensure!(tmp_amount.is_zero(), Error::<T>::InsufficentBalance);
+
+			utxo.retain(|x| !x.amount.is_zero());
+			let utxo: UtxoOf<T> = utxo.try_into().map_err(|_| Error::<T>::TooManyUtxoEntries)?;
+			Self::touch(token_id, who, Self::next_expiry(token, &utxo, now_u64));
+			UTXO::<T>::insert(token_id, who, utxo);
+
+			Ok((taken, expired))
+		}
+	}
+
+	pub trait BrandTransferToken<AccountId> {
+		/// Balance type of brand points.
+		type Balance: AtLeast32BitUnsigned + Copy;
+
+		fn do_transfer(
+			from: AccountId,
+			to: AccountId,
+			brand_id: AccountId,
+			amount: Self::Balance,
+		) -> DispatchResult;
+	}
+
+	impl<T: Config> BrandTransferToken<T::AccountId> for Pallet<T> {
+		type Balance = T::PointBalance;
+
+		fn do_transfer(
+			from: T::AccountId,
+			to: T::AccountId,
+			brand_id: T::AccountId,
+			amount: T::PointBalance,
+		) -> DispatchResult {
+			let brand_token = BrandTokenById::<T>::get(&to);
+			let sum = Self::cal_sum_balance(&brand_id, &from);
+			ensure!(amount <= sum, Error::<T>::InsufficentBalance);
+
+			if brand_token == None {
+				ensure!(P2PTransferAllowed::<T>::get(&brand_id), Error::<T>::P2PTransferDisabled);
+				Self::move_points(&brand_id, &from, &to, amount)?;
+			} else {
+				Self::spend_points(&brand_id, &from, amount)?;
+			}
+
+			Self::deposit_event(Event::Transferred { amount, from, to });
+
+			Ok(())
+		}
+	}
+
+	impl<T: Config> BrandLifecycle<T::AccountId> for Pallet<T> {
+		fn on_brand_transferred(
+			from: &T::AccountId,
+			to: &T::AccountId,
+			budget: &mut u32,
+		) -> DispatchResult {
+			ensure!(!BrandTokenById::<T>::contains_key(to), Error::<T>::AlreadyCreatedToken);
+
+			if let Some(token) = BrandTokenById::<T>::take(from) {
+				let remaining = T::Currency::repatriate_reserved(
+					from,
+					to,
+					token.collateral,
+					BalanceStatus::Reserved,
+				)?;
+				ensure!(remaining.is_zero(), Error::<T>::StakeNotMoved);
+				BrandTokenById::<T>::insert(to, token);
+			}
+
+			let token = BrandTokenById::<T>::get(to);
+			let holders = Self::take_items(UTXO::<T>::drain_prefix(from), budget)?;
+			for (holder, utxo) in holders {
+				// queued sweeps still refer to the old brand account
+				let last_activity = LastActivity::<T>::take(from, &holder);
+				let next = token
+					.as_ref()
+					.and_then(|token| Self::next_expiry(token, &utxo, last_activity));
+				if let Some(next) = next {
+					Self::schedule_expiry(to, &holder, next);
+				}
+				LastActivity::<T>::insert(to, &holder, last_activity);
+				UTXO::<T>::insert(to, holder, utxo);
+			}
+			// every holder was queued under the current policy just now
+			ExpiryRequeueCursor::<T>::remove(from);
+			if P2PTransferAllowed::<T>::take(from) {
+				P2PTransferAllowed::<T>::insert(to, true);
+			}
+			Campaigns::<T>::insert(to, Campaigns::<T>::take(from));
+			ReferralBonus::<T>::insert(to, ReferralBonus::<T>::take(from));
+			ExchangeCaps::<T>::insert(to, ExchangeCaps::<T>::take(from));
+			ExchangeVolume::<T>::insert(to, ExchangeVolume::<T>::take(from));
+			let holds = Self::take_items(Holds::<T>::drain_prefix(from), budget)?;
+			for (hold_id, hold) in holds {
+				// queued timeouts still refer to the old brand account
+				Self::schedule_hold_timeout(to, hold_id, hold.timeout);
+				Holds::<T>::insert(to, hold_id, hold);
+			}
+			if let Some(sponsorship) = Sponsorships::<T>::take(from) {
+				let remaining = T::Currency::repatriate_reserved(
+					from,
+					to,
+					sponsorship.budget,
+					BalanceStatus::Reserved,
+				)?;
+				ensure!(remaining.is_zero(), Error::<T>::StakeNotMoved);
+				Sponsorships::<T>::insert(to, sponsorship);
+			}
+			let allowlist = Self::take_items(SponsorAllowlist::<T>::drain_prefix(from), budget)?;
+			for (who, allowed) in allowlist {
+				SponsorAllowlist::<T>::insert(to, who, allowed);
+			}
+			let sponsored = Self::take_items(SponsoredFees::<T>::drain_prefix(from), budget)?;
+			for (who, paid) in sponsored {
+				SponsoredFees::<T>::insert(to, who, paid);
+			}
+			if let Some(rate) = FeeRates::<T>::take(from) {
+				FeeRates::<T>::insert(to, rate);
+			}
+			if let Some(asset_id) = AssetIds::<T>::take(from) {
+				AssetIds::<T>::insert(to, asset_id);
+				AssetBrands::<T>::insert(asset_id, to);
+			}
+			let frozen = Self::take_items(FrozenAccounts::<T>::drain_prefix(from), budget)?;
+			for (who, is_frozen) in frozen {
+				FrozenAccounts::<T>::insert(to, who, is_frozen);
+			}
+			let clawed_back = Self::take_items(ClawedBack::<T>::drain_prefix(from), budget)?;
+			for (who, total) in clawed_back {
+				ClawedBack::<T>::insert(to, who, total);
+			}
+			let clawbacks = Self::take_items(PendingClawbacks::<T>::drain_prefix(from), budget)?;
+			for (clawback_id, clawback) in clawbacks {
+				PendingClawbacks::<T>::insert(to, clawback_id, clawback);
+			}
+			let referrals = Self::take_items(Referrals::<T>::drain_prefix(from), budget)?;
+			for (referee, referral) in referrals {
+				Referrals::<T>::insert(to, referee, referral);
+			}
+			let counts = Self::take_items(ReferralCounts::<T>::drain_prefix(from), budget)?;
+			for (referrer, count) in counts {
+				ReferralCounts::<T>::insert(to, referrer, count);
+			}
+			let earned = Self::take_items(HasEarned::<T>::drain_prefix(from), budget)?;
+			for (who, has_earned) in earned {
+				HasEarned::<T>::insert(to, who, has_earned);
+			}
+			let rates = Self::take_items(ExchangeRates::<T>::drain_prefix(from), budget)?;
+			for (other, rate) in rates {
+				ExchangeRatesInto::<T>::remove(&other, from);
+				ExchangeRatesInto::<T>::insert(&other, to, ());
+				ExchangeRates::<T>::insert(to, other, rate);
+			}
+			let sources = Self::take_items(ExchangeRatesInto::<T>::drain_prefix(from), budget)?;
+			for (other, ()) in sources {
+				if let Some(rate) = ExchangeRates::<T>::take(&other, from) {
+					ExchangeRates::<T>::insert(&other, to, rate);
+					ExchangeRatesInto::<T>::insert(to, other, ());
+				}
+			}
+			// brand_admin has already moved the coalition membership over to `to`
+			if let Some(coalition_id) = pallet_brand_admin::Pallet::<T>::coalition_of(to) {
+				if CoalitionLedgers::<T>::contains_key(coalition_id, from) {
+					let ledger = CoalitionLedgers::<T>::take(coalition_id, from);
+					CoalitionLedgers::<T>::insert(coalition_id, to, ledger);
+				}
+			}
+
+			Ok(())
+		}
+
+		fn has_live_assets(brand: &T::AccountId) -> bool {
+			BrandTokenById::<T>::get(brand).map_or(false, |token| !token.staked.is_zero()) ||
+				UTXO::<T>::iter_prefix(brand).next().is_some() ||
+				Holds::<T>::iter_prefix(brand).next().is_some() ||
+				PendingClawbacks::<T>::iter_prefix(brand).next().is_some()
+		}
+
+		fn on_brand_removed(brand: &T::AccountId, budget: &mut u32) -> DispatchResult {
+			// settle the brand's coalition position while it is still a member
+			if let Some(coalition_id) = pallet_brand_admin::Pallet::<T>::coalition_of(brand) {
+				let ledger = CoalitionLedgers::<T>::take(coalition_id, brand);
+				let account = pallet_brand_admin::Pallet::<T>::coalition_account(coalition_id);
+				if let Some(token) = BrandTokenById::<T>::get(&account) {
+					Self::settle_ledger(
+						coalition_id,
+						&account,
+						token.collateral_ratio,
+						brand.clone(),
+						ledger,
+					)?;
+				}
+			}
+			if let Some(sponsorship) = Sponsorships::<T>::take(brand) {
+				T::Currency::unreserve(brand, sponsorship.budget);
+			}
+			Self::take_items(SponsorAllowlist::<T>::drain_prefix(brand), budget)?;
+			Self::take_items(SponsoredFees::<T>::drain_prefix(brand), budget)?;
+			let rates = Self::take_items(ExchangeRates::<T>::drain_prefix(brand), budget)?;
+			for (other, _) in rates {
+				ExchangeRatesInto::<T>::remove(&other, brand);
+			}
+			let sources = Self::take_items(ExchangeRatesInto::<T>::drain_prefix(brand), budget)?;
+			for (other, ()) in sources {
+				ExchangeRates::<T>::remove(&other, brand);
+			}
+			Self::take_items(LastActivity::<T>::drain_prefix(brand), budget)?;
+			ExpiryRequeueCursor::<T>::remove(brand);
+
+			let token = match BrandTokenById::<T>::take(brand) {
+				Some(token) => token,
+				None => return Ok(()),
+			};
+			T::Currency::unreserve(brand, token.collateral);
+			P2PTransferAllowed::<T>::remove(brand);
+			FeeRates::<T>::remove(brand);
+			if let Some(asset_id) = AssetIds::<T>::take(brand) {
+				AssetBrands::<T>::remove(asset_id);
+			}
+			Campaigns::<T>::remove(brand);
+			ReferralBonus::<T>::remove(brand);
+			ExchangeCaps::<T>::remove(brand);
+			ExchangeVolume::<T>::remove(brand);
+			Self::take_items(Referrals::<T>::drain_prefix(brand), budget)?;
+			Self::take_items(ReferralCounts::<T>::drain_prefix(brand), budget)?;
+			Self::take_items(HasEarned::<T>::drain_prefix(brand), budget)?;
+			Self::take_items(FrozenAccounts::<T>::drain_prefix(brand), budget)?;
+			Self::take_items(ClawedBack::<T>::drain_prefix(brand), budget)?;
+
+			let held = Self::take_items(Holds::<T>::drain_prefix(brand), budget)?
+				.into_iter()
+				.filter(|(_, hold)| hold.status == HoldStatus::Held)
+				.fold(T::PointBalance::zero(), |sum, (_, hold)| {
+					sum.saturating_add(Self::sum_grants(&hold.grants))
+				});
+			let held = Self::take_items(PendingClawbacks::<T>::drain_prefix(brand), budget)?
+				.into_iter()
+				.fold(held, |sum, (_, clawback)| sum.saturating_add(Self::sum_grants(&clawback.grants)));
+			let burned = Self::take_items(UTXO::<T>::drain_prefix(brand), budget)?
+				.into_iter()
+				.flat_map(|(_, utxo)| utxo.into_iter())
+				.fold(held, |sum, item| sum.saturating_add(item.amount));
+
+			Self::deposit_event(Event::BrandTokenDestroyed { brand_id: brand.clone(), burned });
 
 			Ok(())
 		}
@@ -0,0 +1,399 @@
+//! Storage migrations for the brand token pallet.
+
+use crate::{
+	Amount, AmountOf, BrandToken, BrandTokenById, BrandTokenOf, CoalitionLedger, CoalitionLedgers,
+	Config, ExpiryPolicy, UtxoOf, UTXO,
+};
+use codec::Decode;
+use frame_support::{
+	sp_runtime::{FixedPointNumber, FixedU128, SaturatedConversion},
+	weights::Weight,
+	BoundedVec,
+};
+use sp_std::vec::Vec;
+
+#[derive(Decode)]
+struct OldAmount<Time> {
+	amount: u32,
+	issued_date: Time,
+}
+
+/// A point grant as stored from version 2 until per-grant lifetimes.
+#[derive(Decode)]
+struct V2Amount<Balance, Time> {
+	amount: Balance,
+	issued_date: Time,
+}
+
+#[derive(Decode)]
+struct OldCoalitionLedger {
+	issued: u32,
+	redeemed: u32,
+}
+
+/// Build a token in the current layout. Tokens created before collateral ratios were backed by
+/// one native base unit per point.
+fn upgrade_token<T: Config>(
+	symbol: BoundedVec<u8, T::MaxSymbolLen>,
+	decimals: u8,
+	amount: T::PointBalance,
+	staked: T::PointBalance,
+	default_lifetime: u8,
+) -> BrandTokenOf<T> {
+	BrandToken {
+		symbol,
+		decimals,
+		amount,
+		staked,
+		collateral_ratio: FixedU128::one(),
+		collateral: staked.saturated_into::<u128>().saturated_into(),
+		default_lifetime,
+		expiry_policy: ExpiryPolicy::Lifetime,
+	}
+}
+
+/// Build grants in the current layout, giving them the lifetime of their token. Tokens must be
+/// upgraded first.
+fn upgrade_utxo<T: Config>(
+	brand: &T::AccountId,
+	old: impl IntoIterator<Item = (T::PointBalance, T::Moment)>,
+) -> Vec<AmountOf<T>> {
+	let lifetime = BrandTokenById::<T>::get(brand).map_or(0, |token| token.default_lifetime);
+	old.into_iter()
+		.map(|(amount, issued_date)| Amount { amount, issued_date, lifetime })
+		.collect()
+}
+
+/// Move grants from the version 2 layout to the current one, returning the number of entries.
+fn upgrade_v2_utxo<T: Config>() -> Weight {
+	let mut translated: Weight = 0;
+	UTXO::<T>::translate::<BoundedVec<V2Amount<T::PointBalance, T::Moment>, T::MaxUtxoEntries>, _>(
+		|brand, _, old| {
+			translated += 1;
+			let old = old.into_iter().map(|item| (item.amount, item.issued_date));
+			let utxo: UtxoOf<T> = upgrade_utxo::<T>(&brand, old).try_into().ok()?;
+			Some(utxo)
+		},
+	);
+	translated
+}
+
+/// Move coalition ledgers from `u32` to `T::PointBalance`, returning the number of entries.
+fn upgrade_ledgers<T: Config>() -> Weight {
+	let mut translated: Weight = 0;
+	CoalitionLedgers::<T>::translate::<OldCoalitionLedger, _>(|_, _, old| {
+		translated += 1;
+		Some(CoalitionLedger { issued: old.issued.into(), redeemed: old.redeemed.into() })
+	});
+	translated
+}
+
+pub mod v1 {
+	use super::{upgrade_ledgers, upgrade_token, upgrade_utxo, OldAmount};
+	use crate::{Amount, BrandTokenById, Config, Pallet, UtxoOf, UTXO};
+	use codec::Decode;
+	use frame_support::{
+		sp_runtime::traits::Saturating,
+		traits::{Get, GetStorageVersion, StorageVersion},
+		weights::Weight,
+	};
+	use sp_std::vec::Vec;
+
+	#[derive(Decode)]
+	struct OldBrandToken {
+		symbol: Vec<u8>,
+		amount: u32,
+		staked: u32,
+		default_lifetime: u8,
+	}
+
+	/// Move brand tokens and point grants to bounded storage.
+	///
+	/// Symbols longer than `MaxSymbolLen` are truncated. Holders with more than `MaxUtxoEntries`
+	/// grants get their oldest grants merged into one, dated at the earliest of them, so no
+	/// points are lost and none live longer than before.
+	///
+	/// Values are written in the version 4 layout, so this goes straight to version 4.
+	pub fn migrate<T: Config>() -> Weight {
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+		if on_chain_version >= 1 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut translated: Weight = 0;
+		BrandTokenById::<T>::translate::<OldBrandToken, _>(|_, old| {
+			translated += 1;
+			let mut symbol = old.symbol;
+			symbol.truncate(T::MaxSymbolLen::get() as usize);
+			Some(upgrade_token::<T>(
+				symbol.try_into().unwrap_or_default(),
+				0,
+				old.amount.into(),
+				old.staked.into(),
+				old.default_lifetime,
+			))
+		});
+
+		let max_entries = T::MaxUtxoEntries::get().max(1) as usize;
+		UTXO::<T>::translate::<Vec<OldAmount<T::Moment>>, _>(|brand, _, old| {
+			translated += 1;
+			let old = old.into_iter().map(|item| (item.amount.into(), item.issued_date));
+			let mut utxo = upgrade_utxo::<T>(&brand, old);
+			if utxo.len() > max_entries {
+				let rest = utxo.split_off(utxo.len() - max_entries + 1);
+				let merged = utxo.into_iter().reduce(|acc, item| {
+					let (earliest, issued): (u64, u64) = (acc.issued_date.into(), item.issued_date.into());
+					Amount {
+						amount: acc.amount.saturating_add(item.amount),
+						issued_date: if issued < earliest {
+							item.issued_date
+						} else {
+							acc.issued_date
+						},
+						lifetime: acc.lifetime,
+					}
+				});
+				utxo = merged.into_iter().chain(rest).collect();
+			}
+			let utxo: UtxoOf<T> = utxo.try_into().ok()?;
+			Some(utxo)
+		});
+		translated += upgrade_ledgers::<T>();
+
+		StorageVersion::new(4).put::<Pallet<T>>();
+		T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+	}
+}
+
+pub mod v2 {
+	use super::{upgrade_ledgers, upgrade_token, upgrade_utxo, OldAmount};
+	use crate::{BrandTokenById, Config, Pallet, UtxoOf, UTXO};
+	use codec::Decode;
+	use frame_support::{
+		traits::{Get, GetStorageVersion, StorageVersion},
+		weights::Weight,
+		BoundedVec,
+	};
+
+	#[derive(Decode)]
+	struct OldBrandToken<Symbol> {
+		symbol: Symbol,
+		amount: u32,
+		staked: u32,
+		default_lifetime: u8,
+	}
+
+	/// Move point amounts from `u32` to `T::PointBalance`. Existing tokens get zero decimals, so
+	/// every amount keeps its value.
+	pub fn migrate<T: Config>() -> Weight {
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+		if on_chain_version != 1 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut translated: Weight = 0;
+		BrandTokenById::<T>::translate::<OldBrandToken<BoundedVec<u8, T::MaxSymbolLen>>, _>(
+			|_, old| {
+				translated += 1;
+				Some(upgrade_token::<T>(
+					old.symbol,
+					0,
+					old.amount.into(),
+					old.staked.into(),
+					old.default_lifetime,
+				))
+			},
+		);
+
+		UTXO::<T>::translate::<BoundedVec<OldAmount<T::Moment>, T::MaxUtxoEntries>, _>(
+			|brand, _, old| {
+				translated += 1;
+				let old = old.into_iter().map(|item| (item.amount.into(), item.issued_date));
+				let utxo: UtxoOf<T> = upgrade_utxo::<T>(&brand, old).try_into().ok()?;
+				Some(utxo)
+			},
+		);
+		translated += upgrade_ledgers::<T>();
+
+		StorageVersion::new(4).put::<Pallet<T>>();
+		T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+	}
+}
+
+pub mod v3 {
+	use super::{upgrade_token, upgrade_v2_utxo};
+	use crate::{BrandTokenById, Config, Pallet};
+	use codec::Decode;
+	use frame_support::{
+		traits::{Get, GetStorageVersion, StorageVersion},
+		weights::Weight,
+		BoundedVec,
+	};
+
+	#[derive(Decode)]
+	struct OldBrandToken<Balance, Symbol> {
+		symbol: Symbol,
+		decimals: u8,
+		amount: Balance,
+		staked: Balance,
+		default_lifetime: u8,
+	}
+
+	/// Give every token a collateral ratio of one native base unit per point, which is what
+	/// was reserved for it so far.
+	pub fn migrate<T: Config>() -> Weight {
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+		if on_chain_version != 2 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut translated: Weight = 0;
+		BrandTokenById::<T>::translate::<
+			OldBrandToken<T::PointBalance, BoundedVec<u8, T::MaxSymbolLen>>,
+			_,
+		>(|_, old| {
+			translated += 1;
+			Some(upgrade_token::<T>(
+				old.symbol,
+				old.decimals,
+				old.amount,
+				old.staked,
+				old.default_lifetime,
+			))
+		});
+		translated += upgrade_v2_utxo::<T>();
+
+		StorageVersion::new(4).put::<Pallet<T>>();
+		T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+	}
+}
+
+pub mod v4 {
+	use super::upgrade_v2_utxo;
+	use crate::{BrandToken, BrandTokenById, Config, ExpiryPolicy, Pallet};
+	use codec::Decode;
+	use frame_support::{
+		sp_runtime::FixedU128,
+		traits::{Get, GetStorageVersion, StorageVersion},
+		weights::Weight,
+		BoundedVec,
+	};
+
+	#[derive(Decode)]
+	struct OldBrandToken<Balance, NativeBalance, Symbol> {
+		symbol: Symbol,
+		decimals: u8,
+		amount: Balance,
+		staked: Balance,
+		collateral_ratio: FixedU128,
+		collateral: NativeBalance,
+		default_lifetime: u8,
+	}
+
+	/// Give every grant the lifetime of its token and every token the `Lifetime` expiry policy,
+	/// so points expire exactly as before.
+	pub fn migrate<T: Config>() -> Weight {
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+		if on_chain_version != 3 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut translated: Weight = 0;
+		BrandTokenById::<T>::translate::<OldBrandToken<_, _, BoundedVec<u8, T::MaxSymbolLen>>, _>(
+			|_, old| {
+				translated += 1;
+				Some(BrandToken {
+					symbol: old.symbol,
+					decimals: old.decimals,
+					amount: old.amount,
+					staked: old.staked,
+					collateral_ratio: old.collateral_ratio,
+					collateral: old.collateral,
+					default_lifetime: old.default_lifetime,
+					expiry_policy: ExpiryPolicy::Lifetime,
+				})
+			},
+		);
+		translated += upgrade_v2_utxo::<T>();
+
+		StorageVersion::new(4).put::<Pallet<T>>();
+		T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+	}
+}
+
+pub mod v5 {
+	use crate::{AssetBrands, AssetIds, BrandTokenById, Config, NextAssetId, Pallet};
+	use frame_support::{
+		traits::{Get, GetStorageVersion, StorageVersion},
+		weights::Weight,
+	};
+
+	/// Give every existing token an asset id, under which the `fungibles` traits expose it.
+	pub fn migrate<T: Config>() -> Weight {
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+		if on_chain_version != 4 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut next = NextAssetId::<T>::get();
+		let mut registered: Weight = 0;
+		for brand_id in BrandTokenById::<T>::iter_keys() {
+			AssetIds::<T>::insert(&brand_id, next);
+			AssetBrands::<T>::insert(next, &brand_id);
+			next = next.saturating_add(1);
+			registered += 1;
+		}
+		NextAssetId::<T>::put(next);
+
+		StorageVersion::new(5).put::<Pallet<T>>();
+		T::DbWeight::get().reads_writes(registered + 2, 2 * registered + 2)
+	}
+}
+
+pub mod v6 {
+	use crate::{Config, ExpirySeedCursor, ExpirySeedCursorOf, Pallet};
+	use frame_support::{
+		traits::{Get, GetStorageVersion, StorageVersion},
+		weights::Weight,
+	};
+
+	/// Start queueing the next expiry of every existing holder, which `on_idle` then does a few
+	/// holders per block (see `Pallet::seed_expiry_queue`). Until a holder is reached their
+	/// points still expire lazily, as before.
+	pub fn migrate<T: Config>() -> Weight {
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+		if on_chain_version != 5 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		ExpirySeedCursor::<T>::put(ExpirySeedCursorOf::default());
+
+		StorageVersion::new(6).put::<Pallet<T>>();
+		T::DbWeight::get().reads_writes(1, 2)
+	}
+}
+
+pub mod v7 {
+	use crate::{Config, ExchangeRates, ExchangeRatesInto, Pallet};
+	use frame_support::{
+		traits::{Get, GetStorageVersion, StorageVersion},
+		weights::Weight,
+	};
+
+	/// Build the `ExchangeRatesInto` index from the existing exchange rates.
+	pub fn migrate<T: Config>() -> Weight {
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+		if on_chain_version != 6 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut indexed: Weight = 0;
+		for (from, to, _) in ExchangeRates::<T>::iter() {
+			ExchangeRatesInto::<T>::insert(to, from, ());
+			indexed += 1;
+		}
+
+		StorageVersion::new(7).put::<Pallet<T>>();
+		T::DbWeight::get().reads_writes(indexed + 1, indexed + 1)
+	}
+}
@@ -0,0 +1,1859 @@
+use crate::{
+	mock::*, Amount, AssetBrands, AssetIds, BrandTokenById, Campaign, CampaignBonuses, ClawedBack,
+	CoalitionLedger, CoalitionLedgers, EarnVoucher, EarnVoucherOf, Error, Event as BrandTokenEvent,
+	ExchangeRate, ExchangeRates, ExchangeRatesInto, ExpiryPolicy, ExpiryQueue, ExpiryRequeueCursor,
+	ExpirySeedCursor, FeeRates, FrozenAccounts, HoldStatus, HoldTimeouts, Holds, LastActivity,
+	NextAssetId, PendingClawbacks, ReferralCounts, Referrals, SponsorAllowlist, SponsoredFees,
+	Sponsorships, UsedVoucherNonces, UTXO,
+};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::unhashed,
+	traits::{
+		tokens::fungibles::{Mutate, Transfer},
+		GetStorageVersion, Hooks, StorageVersion,
+	},
+};
+use pallet_brand_admin::{BrandRole, BrandStatus, RemovalMode};
+use sp_runtime::{testing::TestSignature, DispatchError, FixedPointNumber, FixedU128};
+
+/// Length of a lifetime month, as the pallet counts it.
+const MONTH: u64 = 2_592_000;
+
+fn ratio(n: u128) -> FixedU128 {
+	FixedU128::saturating_from_integer(n)
+}
+
+fn create_brand(who: u64) {
+	assert_ok!(BrandAdmin::create_brand(Origin::signed(who), vec![b'A' + who as u8]));
+}
+
+/// Create brand `who` with a token of `staked` points, each backed by one native unit.
+fn create_token(who: u64, staked: u64) {
+	create_brand(who);
+	assert_ok!(BrandToken::create_new_token(
+		Origin::signed(who),
+		b"PTS".to_vec(),
+		0,
+		staked,
+		ratio(1),
+		12
+	));
+}
+
+fn add_operator(brand: u64, operator: u64, role: BrandRole) {
+	assert_ok!(BrandAdmin::add_operator(Origin::signed(brand), brand, operator, role));
+}
+
+fn earn(brand: u64, who: u64, amount: u64) {
+	assert_ok!(BrandToken::earn(Origin::signed(brand), amount, brand, who, None, None));
+}
+
+fn points(brand: u64, who: u64) -> u64 {
+	BrandToken::cal_sum_balance(&brand, &who)
+}
+
+fn pool(brand: u64) -> u64 {
+	BrandTokenById::<Test>::get(brand).unwrap().amount
+}
+
+/// Put brands 1 and 2 in coalition 0 and give it a token.
+fn create_coalition() -> u64 {
+	create_brand(1);
+	create_brand(2);
+	assert_ok!(BrandAdmin::create_coalition(Origin::signed(1), b"Mall".to_vec()));
+	assert_ok!(BrandAdmin::propose_join(Origin::signed(2), 0));
+	assert_ok!(BrandAdmin::approve_membership_change(Origin::signed(1), 0, 2));
+	assert_ok!(BrandToken::create_coalition_token(
+		Origin::signed(1),
+		0,
+		b"MALL".to_vec(),
+		0,
+		ratio(1),
+		12
+	));
+	BrandAdmin::coalition_account(0)
+}
+
+#[test]
+fn create_new_token_reserves_collateral_and_registers_asset() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		assert_ok!(BrandToken::create_new_token(
+			Origin::signed(1),
+			b"PTS".to_vec(),
+			2,
+			500,
+			FixedU128::saturating_from_rational(1, 2),
+			12
+		));
+		System::assert_last_event(
+			BrandTokenEvent::BrandTokenCreated { brand_id: 1, asset_id: 0 }.into(),
+		);
+
+		let token = BrandTokenById::<Test>::get(1).unwrap();
+		assert_eq!((token.amount, token.staked, token.collateral), (500, 500, 250));
+		assert_eq!(token.decimals, 2);
+		assert_eq!(token.expiry_policy, ExpiryPolicy::Lifetime);
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT + 250);
+		assert_eq!(AssetIds::<Test>::get(1), Some(0));
+		assert_eq!(AssetBrands::<Test>::get(0), Some(1));
+		assert_eq!(NextAssetId::<Test>::get(), 1);
+	});
+}
+
+#[test]
+fn create_new_token_errors() {
+	new_test_ext().execute_with(|| {
+		let create = |who: u64, symbol: &[u8], staked: u64, collateral_ratio: FixedU128| {
+			BrandToken::create_new_token(
+				Origin::signed(who),
+				symbol.to_vec(),
+				0,
+				staked,
+				collateral_ratio,
+				12,
+			)
+		};
+		assert_noop!(create(1, b"PTS", 100, ratio(1)), Error::<Test>::BrandNotExist);
+
+		create_brand(1);
+		assert_noop!(create(1, b"POINTS", 100, ratio(1)), Error::<Test>::SymbolTooLong);
+		assert_noop!(
+			create(1, b"PTS", 10_000, ratio(1)),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+		assert_ok!(create(1, b"PTS", 100, ratio(1)));
+		assert_noop!(create(1, b"PTS", 100, ratio(1)), Error::<Test>::AlreadyCreatedToken);
+
+		create_brand(2);
+		assert_noop!(
+			BrandToken::set_min_collateral_ratio(Origin::signed(1), ratio(1)),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(BrandToken::set_min_collateral_ratio(Origin::root(), ratio(1)));
+		System::assert_last_event(
+			BrandTokenEvent::MinCollateralRatioSet { ratio: ratio(1) }.into(),
+		);
+		assert_noop!(
+			create(2, b"PTS", 100, FixedU128::saturating_from_rational(1, 2)),
+			Error::<Test>::CollateralRatioTooLow
+		);
+
+		assert_ok!(BrandAdmin::set_brand_status(Origin::root(), 2, BrandStatus::Suspended));
+		assert_noop!(
+			create(2, b"PTS", 100, ratio(1)),
+			pallet_brand_admin::Error::<Test>::BrandSuspended
+		);
+	});
+}
+
+#[test]
+fn mint_and_burn_move_collateral() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		add_operator(1, 2, BrandRole::Minter);
+
+		assert_ok!(BrandToken::mint(Origin::signed(2), 1, 100));
+		System::assert_last_event(BrandTokenEvent::Mint { amount: 100 }.into());
+		let token = BrandTokenById::<Test>::get(1).unwrap();
+		assert_eq!((token.amount, token.staked, token.collateral), (600, 600, 600));
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT + 600);
+
+		assert_ok!(BrandToken::burn(Origin::signed(1), 300));
+		System::assert_last_event(BrandTokenEvent::Burn { amount: 300 }.into());
+		let token = BrandTokenById::<Test>::get(1).unwrap();
+		assert_eq!((token.amount, token.staked, token.collateral), (300, 300, 300));
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT + 300);
+	});
+}
+
+#[test]
+fn mint_and_burn_errors() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		add_operator(1, 2, BrandRole::Cashier);
+		create_brand(3);
+
+		assert_noop!(
+			BrandToken::mint(Origin::signed(2), 1, 100),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			BrandToken::mint(Origin::signed(3), 3, 100),
+			Error::<Test>::BrandTokenNotFound
+		);
+		assert_noop!(BrandToken::burn(Origin::signed(1), 501), Error::<Test>::InsufficentAmount);
+		assert_noop!(BrandToken::burn(Origin::signed(3), 1), Error::<Test>::BrandTokenNotFound);
+
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::WindDown, 0));
+		assert_noop!(
+			BrandToken::mint(Origin::signed(1), 1, 100),
+			pallet_brand_admin::Error::<Test>::BrandWindingDown
+		);
+	});
+}
+
+#[test]
+fn earn_credits_a_grant_from_the_pool() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		add_operator(1, 2, BrandRole::Cashier);
+		assert_ok!(BrandAdmin::add_outlet(
+			Origin::signed(1),
+			1,
+			b"Main".to_vec(),
+			b"u33d".to_vec(),
+			Some(3)
+		));
+
+		Timestamp::set_timestamp(1_000);
+		earn(1, 5, 100);
+		System::assert_last_event(
+			BrandTokenEvent::Earned { amount: 100, bonus: 0, issued_date: 1_000, outlet: None }
+				.into(),
+		);
+		assert_ok!(BrandToken::earn(Origin::signed(2), 50, 1, 5, None, Some(1)));
+		assert_ok!(BrandToken::earn(Origin::signed(3), 10, 1, 5, Some(0), None));
+		System::assert_last_event(
+			BrandTokenEvent::Earned { amount: 10, bonus: 0, issued_date: 1_000, outlet: Some(0) }
+				.into(),
+		);
+
+		assert_eq!(points(1, 5), 160);
+		assert_eq!(pool(1), 340);
+		let lifetimes: Vec<u8> =
+			UTXO::<Test>::get(1, 5).unwrap().iter().map(|grant| grant.lifetime).collect();
+		assert_eq!(lifetimes, vec![12, 1, 12]);
+		assert!(BrandToken::has_earned(1, 5));
+	});
+}
+
+#[test]
+fn earn_errors() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		assert_ok!(BrandAdmin::add_outlet(
+			Origin::signed(1),
+			1,
+			b"Main".to_vec(),
+			b"u33d".to_vec(),
+			Some(3)
+		));
+
+		assert_noop!(
+			BrandToken::earn(Origin::signed(3), 10, 1, 5, None, None),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			BrandToken::earn(Origin::signed(3), 10, 1, 5, Some(1), None),
+			pallet_brand_admin::Error::<Test>::OutletNotFound
+		);
+		assert_noop!(
+			BrandToken::earn(Origin::signed(1), 0, 1, 5, None, None),
+			Error::<Test>::InvalidAmount
+		);
+		assert_noop!(
+			BrandToken::earn(Origin::signed(1), 501, 1, 5, None, None),
+			Error::<Test>::InsufficentAmount
+		);
+
+		for _ in 0..4 {
+			earn(1, 5, 1);
+		}
+		assert_noop!(
+			BrandToken::earn(Origin::signed(1), 1, 1, 5, None, None),
+			Error::<Test>::TooManyUtxoEntries
+		);
+
+		assert_ok!(BrandAdmin::set_brand_status(Origin::root(), 1, BrandStatus::Suspended));
+		assert_noop!(
+			BrandToken::earn(Origin::signed(1), 1, 1, 6, None, None),
+			pallet_brand_admin::Error::<Test>::BrandSuspended
+		);
+	});
+}
+
+fn voucher(nonce: u64, expiry: u64) -> EarnVoucherOf<Test> {
+	EarnVoucher { brand: 1, beneficiary: 5, amount: 40, nonce, expiry }
+}
+
+fn sign(voucher: &EarnVoucherOf<Test>) -> TestSignature {
+	TestSignature(voucher.brand, BrandToken::voucher_payload(voucher))
+}
+
+#[test]
+fn claim_voucher_credits_the_beneficiary_once() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		let voucher = voucher(7, 10);
+
+		assert_ok!(BrandToken::claim_voucher(Origin::signed(6), voucher.clone(), sign(&voucher)));
+		System::assert_last_event(
+			BrandTokenEvent::VoucherClaimed { brand_id: 1, who: 5, nonce: 7, amount: 40 }.into(),
+		);
+		assert_eq!(points(1, 5), 40);
+		assert_eq!(points(1, 6), 0);
+		assert!(UsedVoucherNonces::<Test>::contains_key(1, 7));
+
+		assert_noop!(
+			BrandToken::claim_voucher(Origin::signed(6), voucher.clone(), sign(&voucher)),
+			Error::<Test>::VoucherAlreadyClaimed
+		);
+	});
+}
+
+#[test]
+fn claim_voucher_errors() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		let valid = voucher(0, 10);
+
+		// signed without the domain tag and genesis hash
+		assert_noop!(
+			BrandToken::claim_voucher(
+				Origin::signed(5),
+				valid.clone(),
+				TestSignature(1, valid.encode())
+			),
+			Error::<Test>::InvalidVoucherSignature
+		);
+		// signed by another account than the brand
+		assert_noop!(
+			BrandToken::claim_voucher(
+				Origin::signed(5),
+				valid.clone(),
+				TestSignature(2, BrandToken::voucher_payload(&valid))
+			),
+			Error::<Test>::InvalidVoucherSignature
+		);
+
+		let expired = voucher(1, 0);
+		assert_noop!(
+			BrandToken::claim_voucher(Origin::signed(5), expired.clone(), sign(&expired)),
+			Error::<Test>::VoucherExpired
+		);
+
+		assert_ok!(BrandAdmin::set_brand_status(Origin::root(), 1, BrandStatus::Suspended));
+		assert_noop!(
+			BrandToken::claim_voucher(Origin::signed(5), valid.clone(), sign(&valid)),
+			pallet_brand_admin::Error::<Test>::BrandSuspended
+		);
+	});
+}
+
+#[test]
+fn vouchers_stay_claimed_when_the_brand_is_registered_again() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		let voucher = voucher(7, 100);
+		assert_ok!(BrandToken::claim_voucher(Origin::signed(6), voucher.clone(), sign(&voucher)));
+
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::WindDown, 10));
+		System::set_block_number(1 + WIND_DOWN_PERIOD);
+		assert_ok!(BrandAdmin::finalize_brand_removal(Origin::signed(6), 1, 10));
+		assert!(UsedVoucherNonces::<Test>::contains_key(1, 7));
+
+		create_token(1, 500);
+		assert_noop!(
+			BrandToken::claim_voucher(Origin::signed(6), voucher.clone(), sign(&voucher)),
+			Error::<Test>::VoucherAlreadyClaimed
+		);
+	});
+}
+
+#[test]
+fn set_collateral_ratio_rebalances_the_reserve() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 100);
+
+		assert_ok!(BrandToken::set_collateral_ratio(Origin::signed(1), ratio(2)));
+		System::assert_last_event(
+			BrandTokenEvent::CollateralRatioSet { brand_id: 1, ratio: ratio(2) }.into(),
+		);
+		assert_eq!(BrandTokenById::<Test>::get(1).unwrap().collateral, 200);
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT + 200);
+
+		let half = FixedU128::saturating_from_rational(1, 2);
+		assert_ok!(BrandToken::set_collateral_ratio(Origin::signed(1), half));
+		assert_eq!(BrandTokenById::<Test>::get(1).unwrap().collateral, 50);
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT + 50);
+
+		create_brand(2);
+		assert_noop!(
+			BrandToken::set_collateral_ratio(Origin::signed(2), ratio(1)),
+			Error::<Test>::BrandTokenNotFound
+		);
+		assert_noop!(
+			BrandToken::set_collateral_ratio(Origin::signed(5), ratio(1)),
+			pallet_brand_admin::Error::<Test>::BrandNotFound
+		);
+		assert_noop!(
+			BrandToken::set_collateral_ratio(Origin::signed(1), ratio(100)),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+		assert_ok!(BrandToken::set_min_collateral_ratio(Origin::root(), ratio(1)));
+		assert_noop!(
+			BrandToken::set_collateral_ratio(Origin::signed(1), half),
+			Error::<Test>::CollateralRatioTooLow
+		);
+	});
+}
+
+#[test]
+fn points_expire_by_policy_and_are_swept() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		add_operator(1, 2, BrandRole::Manager);
+		assert_ok!(BrandToken::set_default_lifetime(Origin::signed(2), 1, 1));
+		System::assert_last_event(
+			BrandTokenEvent::DefaultLifetimeSet { brand_id: 1, lifetime: 1 }.into(),
+		);
+		earn(1, 5, 100);
+		earn(1, 6, 100);
+		assert_ok!(BrandToken::set_expiry_policy(Origin::signed(2), 1, ExpiryPolicy::Never));
+		System::assert_last_event(
+			BrandTokenEvent::ExpiryPolicySet { brand_id: 1, policy: ExpiryPolicy::Never }.into(),
+		);
+
+		Timestamp::set_timestamp(2 * MONTH);
+		assert_eq!(points(1, 5), 100);
+
+		assert_ok!(BrandToken::set_expiry_policy(Origin::signed(1), 1, ExpiryPolicy::Lifetime));
+		assert_eq!(points(1, 5), 0);
+		assert!(UTXO::<Test>::contains_key(1, 5));
+
+		BrandToken::on_idle(1, u64::MAX);
+		assert!(!UTXO::<Test>::contains_key(1, 5));
+		assert!(!UTXO::<Test>::contains_key(1, 6));
+		assert_eq!(ExpiryQueue::<Test>::iter().count(), 0);
+		assert_eq!(pool(1), 500);
+	});
+}
+
+#[test]
+fn changing_the_expiry_policy_queues_existing_holders_again() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		create_token(2, 500);
+		assert_ok!(BrandToken::set_expiry_policy(Origin::signed(1), 1, ExpiryPolicy::Never));
+		earn(1, 5, 100);
+		earn(1, 6, 100);
+		earn(2, 5, 100);
+		assert_eq!(ExpiryQueue::<Test>::iter().count(), 1);
+
+		assert_ok!(BrandToken::set_expiry_policy(
+			Origin::signed(1),
+			1,
+			ExpiryPolicy::FixedDate(MONTH)
+		));
+		assert!(ExpiryRequeueCursor::<Test>::contains_key(1));
+		BrandToken::on_idle(1, u64::MAX);
+		assert_eq!(ExpiryRequeueCursor::<Test>::get(1), None);
+		assert_eq!(ExpiryQueue::<Test>::iter().count(), 3);
+
+		// once the bucket of the fixed date has passed
+		Timestamp::set_timestamp(MONTH + 3600);
+		BrandToken::on_idle(2, u64::MAX);
+		assert!(!UTXO::<Test>::contains_key(1, 5));
+		assert!(!UTXO::<Test>::contains_key(1, 6));
+		assert_eq!(pool(1), 500);
+		assert_eq!(points(2, 5), 100);
+	});
+}
+
+#[test]
+fn token_settings_errors() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		add_operator(1, 2, BrandRole::Cashier);
+		create_brand(3);
+
+		assert_noop!(
+			BrandToken::set_default_lifetime(Origin::signed(2), 1, 1),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			BrandToken::set_expiry_policy(Origin::signed(2), 1, ExpiryPolicy::Rolling),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			BrandToken::set_default_lifetime(Origin::signed(3), 3, 1),
+			Error::<Test>::BrandTokenNotFound
+		);
+		assert_noop!(
+			BrandToken::set_expiry_policy(Origin::signed(3), 3, ExpiryPolicy::Rolling),
+			Error::<Test>::BrandTokenNotFound
+		);
+	});
+}
+
+#[test]
+fn transfer_keeps_the_issue_date() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		Timestamp::set_timestamp(1_000);
+		earn(1, 5, 100);
+		Timestamp::set_timestamp(2_000);
+
+		assert_noop!(
+			BrandToken::transfer(Origin::signed(5), 1, 6, 30),
+			Error::<Test>::P2PTransferDisabled
+		);
+		assert_ok!(BrandToken::set_p2p_transfer(Origin::signed(1), 1, true));
+		System::assert_last_event(
+			BrandTokenEvent::P2PTransferSet { brand_id: 1, allowed: true }.into(),
+		);
+
+		assert_ok!(BrandToken::transfer(Origin::signed(5), 1, 6, 30));
+		System::assert_last_event(
+			BrandTokenEvent::Transferred { amount: 30, from: 5, to: 6 }.into(),
+		);
+		assert_eq!(points(1, 5), 70);
+		assert_eq!(points(1, 6), 30);
+		assert_eq!(UTXO::<Test>::get(1, 6).unwrap()[0].issued_date, 1_000);
+
+		assert_ok!(BrandToken::set_p2p_transfer(Origin::signed(1), 1, false));
+		assert!(!BrandToken::p2p_transfer_allowed(1));
+	});
+}
+
+#[test]
+fn transfer_does_not_renew_rolling_points() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		assert_ok!(BrandToken::set_expiry_policy(Origin::signed(1), 1, ExpiryPolicy::Rolling));
+		assert_ok!(BrandToken::set_p2p_transfer(Origin::signed(1), 1, true));
+		earn(1, 5, 100);
+
+		Timestamp::set_timestamp(6 * MONTH);
+		assert_ok!(BrandToken::transfer(Origin::signed(5), 1, 6, 30));
+		assert_eq!(LastActivity::<Test>::get(1, 6), 0);
+
+		// twelve months after they were issued, whatever the sender did since
+		Timestamp::set_timestamp(12 * MONTH);
+		assert_eq!(points(1, 6), 0);
+		assert_eq!(points(1, 5), 70);
+	});
+}
+
+#[test]
+fn transfer_errors() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		add_operator(1, 2, BrandRole::Cashier);
+		create_brand(3);
+		earn(1, 5, 100);
+		assert_ok!(BrandToken::set_p2p_transfer(Origin::signed(1), 1, true));
+
+		assert_noop!(
+			BrandToken::transfer(Origin::signed(5), 1, 5, 30),
+			Error::<Test>::TransferToSelf
+		);
+		assert_noop!(
+			BrandToken::transfer(Origin::signed(5), 1, 6, 0),
+			Error::<Test>::InvalidAmount
+		);
+		assert_noop!(
+			BrandToken::transfer(Origin::signed(5), 1, 6, 101),
+			Error::<Test>::InsufficentBalance
+		);
+		assert_noop!(
+			BrandToken::set_p2p_transfer(Origin::signed(2), 1, false),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			BrandToken::set_p2p_transfer(Origin::signed(3), 3, true),
+			Error::<Test>::BrandTokenNotFound
+		);
+
+		assert_ok!(BrandAdmin::set_brand_status(Origin::root(), 1, BrandStatus::Suspended));
+		assert_noop!(
+			BrandToken::transfer(Origin::signed(5), 1, 6, 30),
+			pallet_brand_admin::Error::<Test>::BrandSuspended
+		);
+	});
+}
+
+fn campaign(end: u64, budget: u64) -> Campaign<u64> {
+	Campaign { start: 0, end, multiplier: ratio(2), bonus: 5, customer_cap: 50, budget }
+}
+
+#[test]
+fn campaigns_add_capped_bonuses_from_their_budget() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+
+		assert_ok!(BrandToken::create_campaign(Origin::signed(1), 1, campaign(100, 100)));
+		System::assert_last_event(
+			BrandTokenEvent::CampaignCreated { brand_id: 1, campaign_id: 0 }.into(),
+		);
+		assert_eq!(pool(1), 400);
+
+		// doubled and five more, until the customer cap of 50
+		earn(1, 5, 20);
+		earn(1, 5, 20);
+		earn(1, 5, 20);
+		assert_eq!(points(1, 5), 110);
+		assert_eq!(CampaignBonuses::<Test>::get(0, 5), 50);
+		assert_eq!(pool(1), 340);
+
+		assert_ok!(BrandToken::end_campaign(Origin::signed(1), 1, 0));
+		System::assert_last_event(
+			BrandTokenEvent::CampaignEnded { brand_id: 1, campaign_id: 0, refunded: 50 }.into(),
+		);
+		assert_eq!(pool(1), 390);
+		assert!(BrandToken::campaigns(1).is_empty());
+	});
+}
+
+#[test]
+fn ended_campaigns_return_their_budget() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		assert_ok!(BrandToken::create_campaign(Origin::signed(1), 1, campaign(100, 100)));
+
+		Timestamp::set_timestamp(200);
+		assert_ok!(BrandToken::create_campaign(Origin::signed(1), 1, campaign(300, 50)));
+
+		assert_eq!(pool(1), 450);
+		assert_eq!(BrandToken::campaigns(1).len(), 1);
+	});
+}
+
+#[test]
+fn campaign_errors() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		add_operator(1, 2, BrandRole::Cashier);
+		Timestamp::set_timestamp(50);
+
+		let create = |campaign| BrandToken::create_campaign(Origin::signed(1), 1, campaign);
+		assert_noop!(
+			BrandToken::create_campaign(Origin::signed(2), 1, campaign(100, 10)),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			create(Campaign { start: 100, ..campaign(100, 10) }),
+			Error::<Test>::InvalidCampaign
+		);
+		assert_noop!(create(campaign(40, 10)), Error::<Test>::InvalidCampaign);
+		assert_noop!(
+			create(Campaign {
+				multiplier: FixedU128::saturating_from_rational(1, 2),
+				..campaign(100, 10)
+			}),
+			Error::<Test>::InvalidCampaign
+		);
+		assert_noop!(create(campaign(100, 501)), Error::<Test>::InsufficentAmount);
+
+		assert_ok!(create(campaign(100, 10)));
+		assert_ok!(create(campaign(100, 10)));
+		assert_noop!(create(campaign(100, 10)), Error::<Test>::TooManyCampaigns);
+		assert_noop!(
+			BrandToken::end_campaign(Origin::signed(1), 1, 2),
+			Error::<Test>::CampaignNotFound
+		);
+	});
+}
+
+#[test]
+fn first_earn_rewards_the_referrer_and_referee() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		assert_ok!(BrandToken::set_referral_bonus(Origin::signed(1), 1, 10));
+		System::assert_last_event(
+			BrandTokenEvent::ReferralBonusSet { brand_id: 1, bonus: 10 }.into(),
+		);
+
+		assert_ok!(BrandToken::register_referrer(Origin::signed(5), 1, 6));
+		System::assert_last_event(
+			BrandTokenEvent::ReferrerRegistered { brand_id: 1, referee: 5, referrer: 6 }.into(),
+		);
+
+		earn(1, 5, 20);
+		System::assert_has_event(
+			BrandTokenEvent::ReferralRewarded { brand_id: 1, referee: 5, referrer: 6, bonus: 10 }
+				.into(),
+		);
+		assert_eq!(points(1, 5), 30);
+		assert_eq!(points(1, 6), 10);
+		assert_eq!(Referrals::<Test>::get(1, 5).unwrap().reward, Some(10));
+		assert_eq!(ReferralCounts::<Test>::get(1, 6), 1);
+
+		// only the first earn is rewarded
+		earn(1, 5, 20);
+		assert_eq!(points(1, 5), 50);
+		assert_eq!(points(1, 6), 10);
+		assert_eq!(pool(1), 440);
+	});
+}
+
+#[test]
+fn referral_errors() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		create_brand(2);
+		let register = |who: u64, referrer: u64| {
+			BrandToken::register_referrer(Origin::signed(who), 1, referrer)
+		};
+
+		assert_noop!(register(5, 5), Error::<Test>::SelfReferral);
+		assert_noop!(
+			BrandToken::register_referrer(Origin::signed(5), 2, 6),
+			Error::<Test>::BrandTokenNotFound
+		);
+		assert_noop!(
+			BrandToken::set_referral_bonus(Origin::signed(2), 2, 10),
+			Error::<Test>::BrandTokenNotFound
+		);
+
+		assert_ok!(register(5, 6));
+		assert_noop!(register(5, 4), Error::<Test>::ReferrerAlreadySet);
+		assert_noop!(register(6, 5), Error::<Test>::ReferralCycle);
+
+		assert_ok!(register(3, 5));
+		assert_noop!(register(2, 3), Error::<Test>::ReferralChainTooLong);
+
+		earn(1, 4, 10);
+		assert_noop!(register(4, 6), Error::<Test>::AlreadyEarned);
+	});
+}
+
+#[test]
+fn exchange_needs_both_brands_to_agree() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		create_token(2, 500);
+		earn(1, 5, 100);
+
+		assert_ok!(BrandToken::set_exchange_rate(Origin::signed(1), 1, 1, 2, ratio(2)));
+		System::assert_last_event(
+			BrandTokenEvent::ExchangeRateSet { from: 1, to: 2, rate: ratio(2), agreed: false }
+				.into(),
+		);
+		assert!(ExchangeRatesInto::<Test>::contains_key(2, 1));
+		assert_noop!(
+			BrandToken::exchange(Origin::signed(5), 1, 2, 30),
+			Error::<Test>::ExchangeRateNotAgreed
+		);
+
+		assert_ok!(BrandToken::set_exchange_rate(Origin::signed(2), 2, 1, 2, ratio(2)));
+		System::assert_last_event(
+			BrandTokenEvent::ExchangeRateSet { from: 1, to: 2, rate: ratio(2), agreed: true }
+				.into(),
+		);
+		assert_noop!(
+			BrandToken::exchange(Origin::signed(5), 1, 2, 30),
+			Error::<Test>::ExchangeCapExceeded
+		);
+
+		assert_ok!(BrandToken::set_exchange_cap(Origin::signed(1), 1, 100));
+		System::assert_last_event(BrandTokenEvent::ExchangeCapSet { brand_id: 1, cap: 100 }.into());
+		assert_ok!(BrandToken::set_exchange_cap(Origin::signed(2), 2, 100));
+		assert_ok!(BrandToken::exchange(Origin::signed(5), 1, 2, 30));
+		System::assert_last_event(
+			BrandTokenEvent::Exchanged { who: 5, from: 1, to: 2, amount: 30, received: 60 }.into(),
+		);
+		assert_eq!(points(1, 5), 70);
+		assert_eq!(points(2, 5), 60);
+		assert_eq!(pool(1), 430);
+		assert_eq!(pool(2), 440);
+
+		// brand 2 would take in more than its cap of 100 for the day
+		assert_noop!(
+			BrandToken::exchange(Origin::signed(5), 1, 2, 30),
+			Error::<Test>::ExchangeCapExceeded
+		);
+
+		// a new rate needs agreeing on again
+		assert_ok!(BrandToken::set_exchange_rate(Origin::signed(1), 1, 1, 2, ratio(3)));
+		let rate = ExchangeRates::<Test>::get(1, 2).unwrap();
+		assert!(rate.from_agreed && !rate.to_agreed);
+
+		assert_ok!(BrandToken::withdraw_exchange_rate(Origin::signed(2), 2, 1, 2));
+		System::assert_last_event(BrandTokenEvent::ExchangeRateWithdrawn { from: 1, to: 2 }.into());
+		assert!(!ExchangeRates::<Test>::contains_key(1, 2));
+		assert!(!ExchangeRatesInto::<Test>::contains_key(2, 1));
+	});
+}
+
+#[test]
+fn exchange_errors() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		create_token(2, 500);
+		create_brand(3);
+
+		assert_noop!(
+			BrandToken::set_exchange_rate(Origin::signed(1), 1, 1, 1, ratio(2)),
+			Error::<Test>::SameBrand
+		);
+		assert_noop!(
+			BrandToken::set_exchange_rate(Origin::signed(3), 3, 1, 2, ratio(2)),
+			Error::<Test>::NotExchangeParty
+		);
+		assert_noop!(
+			BrandToken::set_exchange_rate(Origin::signed(1), 1, 1, 2, ratio(0)),
+			Error::<Test>::InvalidAmount
+		);
+		assert_noop!(
+			BrandToken::set_exchange_rate(Origin::signed(5), 1, 1, 2, ratio(2)),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			BrandToken::withdraw_exchange_rate(Origin::signed(3), 3, 1, 2),
+			Error::<Test>::NotExchangeParty
+		);
+		assert_noop!(
+			BrandToken::set_exchange_cap(Origin::signed(3), 3, 100),
+			Error::<Test>::BrandTokenNotFound
+		);
+		assert_noop!(BrandToken::exchange(Origin::signed(5), 1, 1, 30), Error::<Test>::SameBrand);
+		assert_noop!(
+			BrandToken::exchange(Origin::signed(5), 1, 2, 0),
+			Error::<Test>::InvalidAmount
+		);
+	});
+}
+
+#[test]
+fn brand_transfer_re_keys_exchange_rates_both_ways() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		create_token(2, 500);
+		create_brand(3);
+		assert_ok!(BrandToken::set_exchange_rate(Origin::signed(1), 1, 1, 2, ratio(2)));
+		assert_ok!(BrandToken::set_exchange_rate(Origin::signed(3), 3, 3, 1, ratio(2)));
+		assert_ok!(BrandAdmin::propose_brand_transfer(Origin::signed(1), 4));
+
+		// both rates
+		assert_noop!(
+			BrandAdmin::accept_brand_transfer(Origin::signed(4), 1, 1),
+			pallet_brand_admin::Error::<Test>::TooManyBrandItems
+		);
+		assert_ok!(BrandAdmin::accept_brand_transfer(Origin::signed(4), 1, 2));
+
+		assert!(ExchangeRates::<Test>::contains_key(4, 2));
+		assert!(ExchangeRatesInto::<Test>::contains_key(2, 4));
+		assert!(!ExchangeRatesInto::<Test>::contains_key(2, 1));
+		assert!(ExchangeRates::<Test>::contains_key(3, 4));
+		assert!(ExchangeRatesInto::<Test>::contains_key(4, 3));
+		assert!(!ExchangeRates::<Test>::contains_key(3, 1));
+	});
+}
+
+#[test]
+fn held_points_can_be_captured_and_refunded() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		add_operator(1, 2, BrandRole::Cashier);
+		earn(1, 5, 100);
+
+		assert_ok!(BrandToken::hold(Origin::signed(5), 1, 40));
+		let timeout = 1 + HOLD_TIMEOUT;
+		System::assert_last_event(
+			BrandTokenEvent::PointsHeld { brand_id: 1, hold_id: 0, who: 5, amount: 40, timeout }
+				.into(),
+		);
+		assert_eq!(points(1, 5), 60);
+		assert!(HoldTimeouts::<Test>::contains_key(timeout, (1, 0)));
+
+		// the customer can't take the points back before the timeout
+		assert_noop!(
+			BrandToken::release(Origin::signed(5), 1, 0),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+
+		assert_ok!(BrandToken::capture(Origin::signed(2), 1, 0, 30));
+		System::assert_last_event(
+			BrandTokenEvent::HoldCaptured { brand_id: 1, hold_id: 0, captured: 30, released: 10 }
+				.into(),
+		);
+		assert_eq!(points(1, 5), 70);
+		assert_eq!(pool(1), 430);
+		let hold = Holds::<Test>::get(1, 0).unwrap();
+		assert_eq!(hold.status, HoldStatus::Captured);
+		assert_eq!(hold.timeout, 1 + REFUND_PERIOD);
+
+		assert_ok!(BrandToken::refund(Origin::signed(1), 1, 0, 10));
+		System::assert_last_event(
+			BrandTokenEvent::HoldRefunded { brand_id: 1, hold_id: 0, amount: 10 }.into(),
+		);
+		assert_eq!(points(1, 5), 80);
+		assert_eq!(pool(1), 420);
+
+		assert_ok!(BrandToken::refund(Origin::signed(1), 1, 0, 20));
+		assert_eq!(points(1, 5), 100);
+		assert!(!Holds::<Test>::contains_key(1, 0));
+		assert_eq!(HoldTimeouts::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn held_points_go_back_on_release_or_timeout() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		earn(1, 5, 100);
+		assert_ok!(BrandToken::hold(Origin::signed(5), 1, 40));
+		assert_ok!(BrandToken::hold(Origin::signed(5), 1, 20));
+
+		assert_ok!(BrandToken::release(Origin::signed(1), 1, 0));
+		System::assert_last_event(
+			BrandTokenEvent::HoldReleased { brand_id: 1, hold_id: 0, amount: 40 }.into(),
+		);
+		assert_eq!(points(1, 5), 80);
+
+		System::set_block_number(1 + HOLD_TIMEOUT);
+		BrandToken::on_idle(1 + HOLD_TIMEOUT, u64::MAX);
+		System::assert_last_event(
+			BrandTokenEvent::HoldReleased { brand_id: 1, hold_id: 1, amount: 20 }.into(),
+		);
+		assert_eq!(points(1, 5), 100);
+		assert_eq!(Holds::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn customers_release_their_holds_after_the_timeout() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		earn(1, 5, 100);
+		assert_ok!(BrandToken::hold(Origin::signed(5), 1, 40));
+
+		System::set_block_number(1 + HOLD_TIMEOUT);
+		assert_ok!(BrandToken::release(Origin::signed(5), 1, 0));
+		assert_eq!(points(1, 5), 100);
+	});
+}
+
+#[test]
+fn hold_errors() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		create_brand(2);
+		earn(1, 5, 100);
+
+		assert_noop!(BrandToken::hold(Origin::signed(5), 1, 0), Error::<Test>::InvalidAmount);
+		assert_noop!(
+			BrandToken::hold(Origin::signed(5), 1, 101),
+			Error::<Test>::InsufficentBalance
+		);
+		assert_noop!(BrandToken::hold(Origin::signed(5), 2, 10), Error::<Test>::BrandTokenNotFound);
+		assert_noop!(BrandToken::release(Origin::signed(1), 1, 0), Error::<Test>::HoldNotFound);
+		assert_noop!(BrandToken::capture(Origin::signed(1), 1, 0, 10), Error::<Test>::HoldNotFound);
+
+		assert_ok!(BrandToken::hold(Origin::signed(5), 1, 40));
+		assert_noop!(
+			BrandToken::capture(Origin::signed(5), 1, 0, 10),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(BrandToken::capture(Origin::signed(1), 1, 0, 0), Error::<Test>::InvalidAmount);
+		assert_noop!(
+			BrandToken::capture(Origin::signed(1), 1, 0, 41),
+			Error::<Test>::HoldAmountTooHigh
+		);
+		assert_noop!(
+			BrandToken::refund(Origin::signed(1), 1, 0, 10),
+			Error::<Test>::HoldNotCaptured
+		);
+
+		assert_ok!(BrandToken::capture(Origin::signed(1), 1, 0, 40));
+		assert_noop!(BrandToken::capture(Origin::signed(1), 1, 0, 10), Error::<Test>::HoldNotHeld);
+		assert_noop!(BrandToken::release(Origin::signed(1), 1, 0), Error::<Test>::HoldNotHeld);
+		assert_noop!(
+			BrandToken::refund(Origin::signed(1), 1, 0, 41),
+			Error::<Test>::HoldAmountTooHigh
+		);
+
+		// the refund period ends
+		System::set_block_number(1 + REFUND_PERIOD);
+		BrandToken::on_idle(1 + REFUND_PERIOD, u64::MAX);
+		System::assert_last_event(BrandTokenEvent::HoldClosed { brand_id: 1, hold_id: 0 }.into());
+		assert_noop!(BrandToken::refund(Origin::signed(1), 1, 0, 10), Error::<Test>::HoldNotFound);
+	});
+}
+
+#[test]
+fn fees_paid_in_points_come_out_of_the_collateral() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		earn(1, 5, 100);
+		assert_eq!(BrandToken::fee_in_points(&1, 10), None);
+
+		assert_ok!(BrandToken::set_fee_rate(Origin::signed(1), 1, Some(ratio(2))));
+		System::assert_last_event(
+			BrandTokenEvent::FeeRateSet { brand_id: 1, rate: Some(ratio(2)) }.into(),
+		);
+		assert_eq!(BrandToken::fee_in_points(&1, 10), Some(20));
+		assert!(BrandToken::can_pay_fee(&1, &5, 10, 20));
+		assert!(!BrandToken::can_pay_fee(&1, &5, 10, 101));
+		assert!(!BrandToken::can_pay_fee(&1, &5, 501, 20));
+
+		let grants = BrandToken::withdraw_fee(&1, &5, 10, 20).unwrap();
+		assert_eq!(points(1, 5), 80);
+		assert_eq!(pool(1), 420);
+
+		// the actual fee was 8, so 4 of the 20 points go back
+		BrandToken::settle_fee(&1, &5, 8, grants, 4);
+		System::assert_last_event(
+			BrandTokenEvent::FeePaidInPoints { brand_id: 1, who: 5, points: 16, fee: 8 }.into(),
+		);
+		assert_eq!(points(1, 5), 84);
+		assert_eq!(pool(1), 416);
+		let token = BrandTokenById::<Test>::get(1).unwrap();
+		assert_eq!((token.collateral, token.staked), (492, 492));
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT + 492);
+
+		assert_noop!(BrandToken::withdraw_fee(&1, &5, 10, 101), Error::<Test>::InsufficentBalance);
+		assert_ok!(BrandToken::set_fee_rate(Origin::signed(1), 1, None));
+		assert!(!FeeRates::<Test>::contains_key(1));
+	});
+}
+
+#[test]
+fn fee_rate_errors() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		add_operator(1, 2, BrandRole::Cashier);
+		create_brand(3);
+
+		assert_noop!(
+			BrandToken::set_fee_rate(Origin::signed(2), 1, Some(ratio(2))),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			BrandToken::set_fee_rate(Origin::signed(3), 3, Some(ratio(2))),
+			Error::<Test>::BrandTokenNotFound
+		);
+	});
+}
+
+#[test]
+fn sponsorships_pay_fees_within_budget_and_daily_limit() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		let call = [0, 1];
+
+		assert_ok!(BrandToken::set_sponsorship(Origin::signed(1), 1, vec![call], 20, false));
+		System::assert_last_event(BrandTokenEvent::SponsorshipSet { brand_id: 1 }.into());
+		assert_ok!(BrandToken::fund_sponsorship(Origin::signed(1), 1, 50));
+		System::assert_last_event(
+			BrandTokenEvent::SponsorshipFunded { brand_id: 1, amount: 50 }.into(),
+		);
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT + 50);
+
+		assert!(BrandToken::can_sponsor(&1, &5, call, 10));
+		assert!(!BrandToken::can_sponsor(&1, &5, [0, 2], 10));
+		assert!(!BrandToken::can_sponsor(&1, &5, call, 21));
+
+		assert_ok!(BrandToken::withdraw_sponsored_fee(&1, &5, call, 15));
+		assert_eq!(BrandToken::sponsorship(1).unwrap().budget, 35);
+		BrandToken::settle_sponsored_fee(&1, &5, 15, 10);
+		System::assert_last_event(
+			BrandTokenEvent::FeeSponsored { brand_id: 1, who: 5, fee: 10 }.into(),
+		);
+		assert_eq!(BrandToken::sponsorship(1).unwrap().budget, 40);
+		assert_eq!(SponsoredFees::<Test>::get(1, 5), (0, 10));
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT + 40);
+
+		// 10 already paid today, against a daily limit of 20
+		assert_noop!(
+			BrandToken::withdraw_sponsored_fee(&1, &5, call, 15),
+			Error::<Test>::SponsorshipExhausted
+		);
+
+		assert_ok!(BrandToken::set_sponsorship(Origin::signed(1), 1, vec![call], 20, true));
+		assert_eq!(BrandToken::sponsorship(1).unwrap().budget, 40);
+		assert!(!BrandToken::can_sponsor(&1, &6, call, 5));
+		assert_ok!(BrandToken::set_sponsor_allowlist(Origin::signed(1), 1, 6, true));
+		System::assert_last_event(
+			BrandTokenEvent::SponsorAllowlistSet { brand_id: 1, who: 6, allowed: true }.into(),
+		);
+		assert!(BrandToken::can_sponsor(&1, &6, call, 5));
+
+		assert_ok!(BrandToken::withdraw_sponsorship(Origin::signed(1), 1, 10));
+		System::assert_last_event(
+			BrandTokenEvent::SponsorshipWithdrawn { brand_id: 1, amount: 10 }.into(),
+		);
+		assert_ok!(BrandToken::end_sponsorship(Origin::signed(1), 1));
+		System::assert_last_event(
+			BrandTokenEvent::SponsorshipEnded { brand_id: 1, refunded: 30 }.into(),
+		);
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT);
+		assert!(!Sponsorships::<Test>::contains_key(1));
+		assert!(!SponsorAllowlist::<Test>::contains_key(1, 6));
+		assert!(!SponsoredFees::<Test>::contains_key(1, 5));
+	});
+}
+
+#[test]
+fn sponsorship_errors() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		add_operator(1, 2, BrandRole::Cashier);
+
+		assert_noop!(
+			BrandToken::fund_sponsorship(Origin::signed(1), 1, 50),
+			Error::<Test>::SponsorshipNotFound
+		);
+		assert_noop!(
+			BrandToken::withdraw_sponsorship(Origin::signed(1), 1, 50),
+			Error::<Test>::SponsorshipNotFound
+		);
+		assert_noop!(
+			BrandToken::end_sponsorship(Origin::signed(1), 1),
+			Error::<Test>::SponsorshipNotFound
+		);
+		assert_noop!(
+			BrandToken::set_sponsorship(
+				Origin::signed(1),
+				1,
+				vec![[0, 1], [0, 2], [0, 3]],
+				20,
+				false
+			),
+			Error::<Test>::TooManySponsoredCalls
+		);
+		assert_noop!(
+			BrandToken::set_sponsorship(Origin::signed(2), 1, vec![[0, 1]], 20, false),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(
+			BrandToken::set_sponsor_allowlist(Origin::signed(2), 1, 5, true),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+
+		assert_ok!(BrandToken::set_sponsorship(Origin::signed(1), 1, vec![[0, 1]], 20, false));
+		assert_noop!(
+			BrandToken::withdraw_sponsorship(Origin::signed(1), 1, 1),
+			Error::<Test>::InsufficentBalance
+		);
+		assert_noop!(
+			BrandToken::fund_sponsorship(Origin::signed(1), 1, 10_000),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+	});
+}
+
+#[test]
+fn frozen_accounts_cannot_spend_or_move_points() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		add_operator(1, 2, BrandRole::Cashier);
+		earn(1, 5, 100);
+		assert_ok!(BrandToken::set_p2p_transfer(Origin::signed(1), 1, true));
+
+		assert_noop!(
+			BrandToken::freeze(Origin::signed(2), 1, 5),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_ok!(BrandToken::freeze(Origin::signed(1), 1, 5));
+		System::assert_last_event(BrandTokenEvent::AccountFrozen { brand_id: 1, who: 5 }.into());
+		assert!(FrozenAccounts::<Test>::get(1, 5));
+
+		assert_noop!(BrandToken::hold(Origin::signed(5), 1, 10), Error::<Test>::AccountFrozen);
+		assert_noop!(
+			BrandToken::transfer(Origin::signed(5), 1, 6, 10),
+			Error::<Test>::AccountFrozen
+		);
+
+		assert_noop!(
+			BrandToken::thaw(Origin::signed(2), 1, 5),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_ok!(BrandToken::thaw(Origin::signed(1), 1, 5));
+		System::assert_last_event(BrandTokenEvent::AccountThawed { brand_id: 1, who: 5 }.into());
+		assert_ok!(BrandToken::transfer(Origin::signed(5), 1, 6, 10));
+	});
+}
+
+#[test]
+fn clawback_takes_the_newest_points_back_into_the_pool() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		earn(1, 5, 100);
+		Timestamp::set_timestamp(10);
+		earn(1, 5, 50);
+
+		assert_ok!(BrandToken::clawback(Origin::signed(1), 1, 5, 60, b"fraud".to_vec()));
+		System::assert_last_event(
+			BrandTokenEvent::ClawedBack {
+				brand_id: 1,
+				who: 5,
+				amount: 60,
+				reason: b"fraud".to_vec(),
+			}
+			.into(),
+		);
+		assert_eq!(points(1, 5), 90);
+		let grants = UTXO::<Test>::get(1, 5).unwrap();
+		assert_eq!(grants.len(), 1);
+		assert_eq!((grants[0].amount, grants[0].issued_date), (90, 0));
+		assert_eq!(ClawedBack::<Test>::get(1, 5), 60);
+		assert_eq!(pool(1), 410);
+	});
+}
+
+#[test]
+fn clawback_errors() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		add_operator(1, 2, BrandRole::Cashier);
+		create_brand(3);
+		earn(1, 5, 100);
+
+		assert_noop!(
+			BrandToken::set_clawback_limits(Origin::signed(1), Some(80), 0),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(BrandToken::set_clawback_limits(Origin::root(), Some(80), 0));
+		System::assert_last_event(
+			BrandTokenEvent::ClawbackLimitsSet { cap: Some(80), appeal_window: 0 }.into(),
+		);
+
+		let clawback = |brand: u64, amount: u64, reason: &[u8]| {
+			BrandToken::clawback(Origin::signed(brand), brand, 5, amount, reason.to_vec())
+		};
+		assert_noop!(
+			BrandToken::clawback(Origin::signed(2), 1, 5, 10, b"fraud".to_vec()),
+			pallet_brand_admin::Error::<Test>::NotBrandOperator
+		);
+		assert_noop!(clawback(1, 0, b"fraud"), Error::<Test>::InvalidAmount);
+		assert_noop!(clawback(1, 10, b"fraudulent"), Error::<Test>::ReasonTooLong);
+		assert_noop!(clawback(3, 10, b"fraud"), Error::<Test>::BrandTokenNotFound);
+		assert_ok!(clawback(1, 60, b"fraud"));
+		assert_noop!(clawback(1, 30, b"fraud"), Error::<Test>::ClawbackCapExceeded);
+
+		assert_ok!(BrandToken::set_clawback_limits(Origin::root(), None, 0));
+		assert_noop!(clawback(1, 41, b"fraud"), Error::<Test>::InsufficentBalance);
+	});
+}
+
+#[test]
+fn appealed_clawbacks_are_decided_by_the_clawback_origin() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		earn(1, 5, 100);
+		assert_ok!(BrandToken::set_clawback_limits(Origin::root(), None, 5));
+
+		assert_ok!(BrandToken::clawback(Origin::signed(1), 1, 5, 40, b"fraud".to_vec()));
+		System::assert_last_event(
+			BrandTokenEvent::ClawbackScheduled {
+				brand_id: 1,
+				clawback_id: 0,
+				who: 5,
+				amount: 40,
+				execute_at: 6,
+			}
+			.into(),
+		);
+		assert_eq!(points(1, 5), 60);
+		assert_eq!(pool(1), 400);
+
+		assert_noop!(
+			BrandToken::execute_clawback(Origin::signed(1), 1, 0),
+			Error::<Test>::AppealWindowOpen
+		);
+		assert_noop!(
+			BrandToken::resolve_clawback_appeal(Origin::root(), 1, 0, true),
+			Error::<Test>::ClawbackNotAppealed
+		);
+		assert_noop!(
+			BrandToken::appeal_clawback(Origin::signed(6), 1, 0),
+			Error::<Test>::NotClawbackSubject
+		);
+		assert_ok!(BrandToken::appeal_clawback(Origin::signed(5), 1, 0));
+		System::assert_last_event(
+			BrandTokenEvent::ClawbackAppealed { brand_id: 1, clawback_id: 0 }.into(),
+		);
+		assert_noop!(
+			BrandToken::appeal_clawback(Origin::signed(5), 1, 0),
+			Error::<Test>::ClawbackAppealed
+		);
+
+		System::set_block_number(6);
+		assert_noop!(
+			BrandToken::execute_clawback(Origin::signed(1), 1, 0),
+			Error::<Test>::ClawbackAppealed
+		);
+		assert_noop!(
+			BrandToken::resolve_clawback_appeal(Origin::signed(1), 1, 0, true),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(BrandToken::resolve_clawback_appeal(Origin::root(), 1, 0, true));
+		System::assert_last_event(
+			BrandTokenEvent::ClawbackReversed { brand_id: 1, clawback_id: 0, amount: 40 }.into(),
+		);
+		assert_eq!(points(1, 5), 100);
+		assert_eq!(ClawedBack::<Test>::get(1, 5), 0);
+		assert!(!PendingClawbacks::<Test>::contains_key(1, 0));
+		assert_noop!(
+			BrandToken::resolve_clawback_appeal(Origin::root(), 1, 0, true),
+			Error::<Test>::ClawbackNotFound
+		);
+	});
+}
+
+#[test]
+fn unappealed_clawbacks_execute_after_the_window() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		earn(1, 5, 100);
+		assert_ok!(BrandToken::set_clawback_limits(Origin::root(), None, 5));
+		assert_ok!(BrandToken::clawback(Origin::signed(1), 1, 5, 40, b"fraud".to_vec()));
+
+		System::set_block_number(6);
+		assert_noop!(
+			BrandToken::appeal_clawback(Origin::signed(5), 1, 0),
+			Error::<Test>::AppealWindowClosed
+		);
+		assert_ok!(BrandToken::execute_clawback(Origin::signed(1), 1, 0));
+		System::assert_last_event(
+			BrandTokenEvent::ClawedBack {
+				brand_id: 1,
+				who: 5,
+				amount: 40,
+				reason: b"fraud".to_vec(),
+			}
+			.into(),
+		);
+		assert_eq!(pool(1), 440);
+		assert_eq!(ClawedBack::<Test>::get(1, 5), 40);
+		assert_noop!(
+			BrandToken::execute_clawback(Origin::signed(1), 1, 0),
+			Error::<Test>::ClawbackNotFound
+		);
+	});
+}
+
+#[test]
+fn claim_refund_pays_out_the_collateral_share() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		earn(1, 5, 100);
+		earn(1, 6, 100);
+
+		assert_noop!(
+			BrandToken::claim_refund(Origin::signed(5), 1),
+			Error::<Test>::BrandNotWindingDown
+		);
+		assert_ok!(BrandToken::freeze(Origin::signed(1), 1, 6));
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::WindDown, 0));
+		assert_noop!(
+			BrandToken::earn(Origin::signed(1), 10, 1, 5, None, None),
+			pallet_brand_admin::Error::<Test>::BrandWindingDown
+		);
+
+		assert_ok!(BrandToken::claim_refund(Origin::signed(5), 1));
+		System::assert_last_event(
+			BrandTokenEvent::Refunded { brand_id: 1, who: 5, amount: 100, collateral: 100 }.into(),
+		);
+		assert_eq!(Balances::free_balance(5), 1_100);
+		assert_eq!(Balances::reserved_balance(1), BRAND_DEPOSIT + 400);
+		let token = BrandTokenById::<Test>::get(1).unwrap();
+		assert_eq!((token.staked, token.collateral), (400, 400));
+		assert!(!UTXO::<Test>::contains_key(1, 5));
+
+		assert_noop!(
+			BrandToken::claim_refund(Origin::signed(5), 1),
+			Error::<Test>::InsufficentBalance
+		);
+		assert_noop!(BrandToken::claim_refund(Origin::signed(6), 1), Error::<Test>::AccountFrozen);
+	});
+}
+
+#[test]
+fn coalition_points_are_settled_between_members() {
+	new_test_ext().execute_with(|| {
+		let account = create_coalition();
+		System::assert_last_event(
+			BrandTokenEvent::BrandTokenCreated { brand_id: account, asset_id: 0 }.into(),
+		);
+
+		assert_ok!(BrandToken::fund_coalition(Origin::signed(1), 0, 1, 100));
+		System::assert_last_event(
+			BrandTokenEvent::CoalitionFunded { coalition_id: 0, brand_id: 1, amount: 100 }.into(),
+		);
+		assert_eq!(Balances::reserved_balance(account), 100);
+		assert_eq!(Balances::free_balance(1), 1_000 - BRAND_DEPOSIT - 100);
+
+		assert_ok!(BrandToken::earn_coalition(Origin::signed(1), 0, 1, 5, 30));
+		System::assert_last_event(
+			BrandTokenEvent::CoalitionEarned { coalition_id: 0, brand_id: 1, who: 5, amount: 30 }
+				.into(),
+		);
+		assert_eq!(points(account, 5), 30);
+
+		assert_ok!(BrandToken::redeem_coalition(Origin::signed(5), 0, 2, 30));
+		System::assert_last_event(
+			BrandTokenEvent::CoalitionRedeemed { coalition_id: 0, brand_id: 2, who: 5, amount: 30 }
+				.into(),
+		);
+		assert_eq!(points(account, 5), 0);
+		assert_eq!(
+			CoalitionLedgers::<Test>::get(0, 1),
+			CoalitionLedger { issued: 30, redeemed: 0 }
+		);
+		assert_eq!(
+			CoalitionLedgers::<Test>::get(0, 2),
+			CoalitionLedger { issued: 0, redeemed: 30 }
+		);
+
+		assert_ok!(BrandToken::settle_coalition(Origin::signed(2), 0));
+		System::assert_has_event(
+			BrandTokenEvent::CoalitionSettled {
+				coalition_id: 0,
+				brand_id: 1,
+				paid: 30,
+				received: 0,
+			}
+			.into(),
+		);
+		System::assert_last_event(
+			BrandTokenEvent::CoalitionSettled {
+				coalition_id: 0,
+				brand_id: 2,
+				paid: 0,
+				received: 30,
+			}
+			.into(),
+		);
+		assert_eq!(Balances::free_balance(1), 1_000 - BRAND_DEPOSIT - 130);
+		assert_eq!(Balances::free_balance(2), 1_000 - BRAND_DEPOSIT + 30);
+		assert_eq!(CoalitionLedgers::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn coalition_errors() {
+	new_test_ext().execute_with(|| {
+		create_brand(1);
+		create_brand(3);
+		assert_ok!(BrandAdmin::create_coalition(Origin::signed(1), b"Mall".to_vec()));
+
+		assert_noop!(
+			BrandToken::fund_coalition(Origin::signed(1), 0, 1, 100),
+			Error::<Test>::CoalitionTokenNotFound
+		);
+		assert_noop!(
+			BrandToken::settle_coalition(Origin::signed(1), 0),
+			Error::<Test>::CoalitionTokenNotFound
+		);
+		assert_noop!(
+			BrandToken::create_coalition_token(
+				Origin::signed(3),
+				0,
+				b"MALL".to_vec(),
+				0,
+				ratio(1),
+				12
+			),
+			pallet_brand_admin::Error::<Test>::NotCoalitionMember
+		);
+		assert_noop!(
+			BrandToken::create_coalition_token(
+				Origin::signed(1),
+				0,
+				b"MALLS".to_vec(),
+				0,
+				ratio(1),
+				12
+			),
+			Error::<Test>::SymbolTooLong
+		);
+
+		assert_ok!(BrandToken::create_coalition_token(
+			Origin::signed(1),
+			0,
+			b"MALL".to_vec(),
+			0,
+			ratio(1),
+			12
+		));
+		assert_noop!(
+			BrandToken::create_coalition_token(
+				Origin::signed(1),
+				0,
+				b"MALL".to_vec(),
+				0,
+				ratio(1),
+				12
+			),
+			Error::<Test>::AlreadyCreatedToken
+		);
+		assert_noop!(
+			BrandToken::fund_coalition(Origin::signed(3), 0, 3, 100),
+			pallet_brand_admin::Error::<Test>::NotCoalitionMember
+		);
+		assert_noop!(
+			BrandToken::earn_coalition(Origin::signed(1), 0, 1, 5, 1),
+			Error::<Test>::InsufficentAmount
+		);
+		assert_noop!(
+			BrandToken::redeem_coalition(Origin::signed(5), 0, 3, 1),
+			pallet_brand_admin::Error::<Test>::NotCoalitionMember
+		);
+		assert_noop!(
+			BrandToken::settle_coalition(Origin::signed(3), 0),
+			pallet_brand_admin::Error::<Test>::NotCoalitionMember
+		);
+	});
+}
+
+#[test]
+fn coalition_points_need_an_amount_and_a_member_in_good_standing() {
+	new_test_ext().execute_with(|| {
+		create_coalition();
+		assert_ok!(BrandToken::fund_coalition(Origin::signed(1), 0, 1, 100));
+		assert_ok!(BrandToken::earn_coalition(Origin::signed(1), 0, 1, 5, 30));
+
+		assert_noop!(
+			BrandToken::earn_coalition(Origin::signed(1), 0, 1, 5, 0),
+			Error::<Test>::InvalidAmount
+		);
+		assert_noop!(
+			BrandToken::redeem_coalition(Origin::signed(5), 0, 2, 0),
+			Error::<Test>::InvalidAmount
+		);
+
+		assert_ok!(BrandAdmin::set_brand_status(Origin::root(), 2, BrandStatus::Suspended));
+		assert_noop!(
+			BrandToken::redeem_coalition(Origin::signed(5), 0, 2, 10),
+			pallet_brand_admin::Error::<Test>::BrandSuspended
+		);
+		assert_ok!(BrandToken::redeem_coalition(Origin::signed(5), 0, 1, 10));
+	});
+}
+
+#[test]
+fn brand_transfer_moves_the_token_and_its_state() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		create_token(2, 500);
+		earn(1, 5, 100);
+		assert_ok!(BrandAdmin::propose_brand_transfer(Origin::signed(1), 4));
+
+		// the holder and its first earn
+		assert_noop!(
+			BrandAdmin::accept_brand_transfer(Origin::signed(4), 1, 1),
+			pallet_brand_admin::Error::<Test>::TooManyBrandItems
+		);
+		assert_ok!(BrandAdmin::accept_brand_transfer(Origin::signed(4), 1, 2));
+
+		assert!(!BrandTokenById::<Test>::contains_key(1));
+		assert_eq!(BrandTokenById::<Test>::get(4).unwrap().collateral, 500);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(4), BRAND_DEPOSIT + 500);
+		assert_eq!(points(4, 5), 100);
+		assert_eq!(AssetIds::<Test>::get(4), Some(0));
+		assert_eq!(AssetBrands::<Test>::get(0), Some(4));
+	});
+}
+
+#[test]
+fn brand_transfer_moves_the_coalition_ledger() {
+	new_test_ext().execute_with(|| {
+		create_coalition();
+		assert_ok!(BrandToken::fund_coalition(Origin::signed(1), 0, 1, 100));
+		assert_ok!(BrandToken::earn_coalition(Origin::signed(1), 0, 1, 5, 30));
+
+		assert_ok!(BrandAdmin::propose_brand_transfer(Origin::signed(1), 4));
+		assert_ok!(BrandAdmin::accept_brand_transfer(Origin::signed(4), 1, 10));
+
+		assert!(!CoalitionLedgers::<Test>::contains_key(0, 1));
+		assert_eq!(
+			CoalitionLedgers::<Test>::get(0, 4),
+			CoalitionLedger { issued: 30, redeemed: 0 }
+		);
+	});
+}
+
+#[test]
+fn brand_removal_clears_the_token_and_its_state() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		create_token(2, 0);
+		assert_ok!(BrandToken::set_exchange_rate(Origin::signed(2), 2, 2, 1, ratio(2)));
+		assert_ok!(BrandToken::set_exchange_rate(Origin::signed(2), 2, 1, 2, ratio(2)));
+
+		assert_noop!(
+			BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::Strict, 10),
+			pallet_brand_admin::Error::<Test>::BrandHasLiveAssets
+		);
+
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(2), RemovalMode::Strict, 10));
+		System::assert_has_event(
+			BrandTokenEvent::BrandTokenDestroyed { brand_id: 2, burned: 0 }.into(),
+		);
+		assert!(!BrandTokenById::<Test>::contains_key(2));
+		assert_eq!(AssetIds::<Test>::get(2), None);
+		assert_eq!(AssetBrands::<Test>::get(1), None);
+		assert_eq!(ExchangeRates::<Test>::iter().count(), 0);
+		assert_eq!(ExchangeRatesInto::<Test>::iter().count(), 0);
+		assert_eq!(Balances::reserved_balance(2), 0);
+	});
+}
+
+#[test]
+fn brand_removal_settles_the_coalition_ledger() {
+	new_test_ext().execute_with(|| {
+		let account = create_coalition();
+		assert_ok!(BrandToken::fund_coalition(Origin::signed(2), 0, 2, 100));
+		assert_ok!(BrandToken::earn_coalition(Origin::signed(1), 0, 1, 5, 30));
+
+		assert_ok!(BrandAdmin::remove_brand(Origin::signed(1), RemovalMode::Strict, 10));
+		System::assert_has_event(
+			BrandTokenEvent::CoalitionSettled {
+				coalition_id: 0,
+				brand_id: 1,
+				paid: 30,
+				received: 0,
+			}
+			.into(),
+		);
+		assert_eq!(Balances::free_balance(1), 1_000 - 30);
+		assert_eq!(Balances::free_balance(account), 30);
+		assert!(!CoalitionLedgers::<Test>::contains_key(0, 1));
+	});
+}
+
+#[test]
+fn fungibles_mint_refuses_inactive_brands() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+
+		assert_ok!(BrandToken::mint_into(0, &5, 10));
+		assert_eq!(points(1, 5), 10);
+		assert_eq!(BrandTokenById::<Test>::get(1).unwrap().staked, 510);
+
+		assert_ok!(BrandAdmin::set_brand_status(Origin::root(), 1, BrandStatus::Suspended));
+		assert_noop!(
+			BrandToken::mint_into(0, &5, 10),
+			pallet_brand_admin::Error::<Test>::BrandSuspended
+		);
+		assert_noop!(BrandToken::mint_into(1, &5, 10), Error::<Test>::BrandTokenNotFound);
+	});
+}
+
+#[test]
+fn fungibles_transfer_refuses_suspended_brands() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		assert_ok!(BrandToken::set_p2p_transfer(Origin::signed(1), 1, true));
+		earn(1, 5, 100);
+
+		assert_ok!(<BrandToken as Transfer<u64>>::transfer(0, &5, &6, 30, false));
+		assert_eq!(points(1, 6), 30);
+
+		assert_ok!(BrandAdmin::set_brand_status(Origin::root(), 1, BrandStatus::Suspended));
+		assert_noop!(
+			<BrandToken as Transfer<u64>>::transfer(0, &5, &6, 30, false),
+			pallet_brand_admin::Error::<Test>::BrandSuspended
+		);
+		assert_noop!(
+			<BrandToken as Transfer<u64>>::transfer(0, &1, &6, 30, false),
+			pallet_brand_admin::Error::<Test>::BrandSuspended
+		);
+	});
+}
+
+#[test]
+fn migration_v1_bounds_tokens_and_merges_oldest_grants() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<BrandToken>();
+		unhashed::put(
+			&BrandTokenById::<Test>::hashed_key_for(1),
+			&(b"POINTS".to_vec(), 400u32, 500u32, 12u8),
+		);
+		let grants: Vec<(u32, u64)> = (1..=6).map(|i| (10 * i as u32, 100 * i)).collect();
+		unhashed::put(&UTXO::<Test>::hashed_key_for(1, 5), &grants);
+		unhashed::put(&CoalitionLedgers::<Test>::hashed_key_for(0, 1), &(30u32, 10u32));
+
+		crate::migrations::v1::migrate::<Test>();
+
+		let token = BrandTokenById::<Test>::get(1).unwrap();
+		assert_eq!(token.symbol.into_inner(), b"POIN".to_vec());
+		assert_eq!((token.amount, token.staked, token.collateral), (400, 500, 500));
+		assert_eq!(token.collateral_ratio, ratio(1));
+		let grants: Vec<(u64, u64)> = UTXO::<Test>::get(1, 5)
+			.unwrap()
+			.iter()
+			.map(|grant| (grant.amount, grant.issued_date))
+			.collect();
+		assert_eq!(grants, vec![(60, 100), (40, 400), (50, 500), (60, 600)]);
+		assert_eq!(
+			CoalitionLedgers::<Test>::get(0, 1),
+			CoalitionLedger { issued: 30, redeemed: 10 }
+		);
+		assert_eq!(BrandToken::on_chain_storage_version(), 4);
+	});
+}
+
+#[test]
+fn migration_v2_widens_amounts() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(1).put::<BrandToken>();
+		unhashed::put(
+			&BrandTokenById::<Test>::hashed_key_for(1),
+			&(b"PTS".to_vec(), 400u32, 500u32, 6u8),
+		);
+		unhashed::put(&UTXO::<Test>::hashed_key_for(1, 5), &vec![(100u32, 1_000u64)]);
+		unhashed::put(&CoalitionLedgers::<Test>::hashed_key_for(0, 1), &(30u32, 10u32));
+
+		crate::migrations::v2::migrate::<Test>();
+
+		let token = BrandTokenById::<Test>::get(1).unwrap();
+		assert_eq!((token.decimals, token.amount, token.staked), (0, 400, 500));
+		assert_eq!(
+			UTXO::<Test>::get(1, 5).unwrap().into_inner(),
+			vec![Amount { amount: 100, issued_date: 1_000, lifetime: 6 }]
+		);
+		assert_eq!(
+			CoalitionLedgers::<Test>::get(0, 1),
+			CoalitionLedger { issued: 30, redeemed: 10 }
+		);
+		assert_eq!(BrandToken::on_chain_storage_version(), 4);
+	});
+}
+
+#[test]
+fn migration_v3_backs_tokens_one_to_one() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(2).put::<BrandToken>();
+		unhashed::put(
+			&BrandTokenById::<Test>::hashed_key_for(1),
+			&(b"PTS".to_vec(), 2u8, 400u64, 500u64, 6u8),
+		);
+		unhashed::put(&UTXO::<Test>::hashed_key_for(1, 5), &vec![(100u64, 1_000u64)]);
+
+		crate::migrations::v3::migrate::<Test>();
+
+		let token = BrandTokenById::<Test>::get(1).unwrap();
+		assert_eq!(token.decimals, 2);
+		assert_eq!((token.collateral_ratio, token.collateral), (ratio(1), 500));
+		assert_eq!(UTXO::<Test>::get(1, 5).unwrap()[0].lifetime, 6);
+		assert_eq!(BrandToken::on_chain_storage_version(), 4);
+	});
+}
+
+#[test]
+fn migration_v4_adds_lifetimes_and_expiry_policy() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(3).put::<BrandToken>();
+		unhashed::put(
+			&BrandTokenById::<Test>::hashed_key_for(1),
+			&(b"PTS".to_vec(), 2u8, 400u64, 500u64, ratio(2), 1_000u64, 6u8),
+		);
+		unhashed::put(&UTXO::<Test>::hashed_key_for(1, 5), &vec![(100u64, 1_000u64)]);
+
+		crate::migrations::v4::migrate::<Test>();
+
+		let token = BrandTokenById::<Test>::get(1).unwrap();
+		assert_eq!((token.collateral_ratio, token.collateral), (ratio(2), 1_000));
+		assert_eq!(token.expiry_policy, ExpiryPolicy::Lifetime);
+		assert_eq!(UTXO::<Test>::get(1, 5).unwrap()[0].lifetime, 6);
+		assert_eq!(BrandToken::on_chain_storage_version(), 4);
+	});
+}
+
+#[test]
+fn migration_v5_registers_assets() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		create_token(2, 500);
+		let _ = AssetIds::<Test>::remove_all(None);
+		let _ = AssetBrands::<Test>::remove_all(None);
+		NextAssetId::<Test>::kill();
+		StorageVersion::new(4).put::<BrandToken>();
+
+		crate::migrations::v5::migrate::<Test>();
+
+		let mut brands: Vec<u64> = AssetBrands::<Test>::iter_values().collect();
+		brands.sort_unstable();
+		assert_eq!(brands, vec![1, 2]);
+		assert!(AssetIds::<Test>::contains_key(1) && AssetIds::<Test>::contains_key(2));
+		assert_eq!(NextAssetId::<Test>::get(), 2);
+		assert_eq!(BrandToken::on_chain_storage_version(), 5);
+	});
+}
+
+#[test]
+fn migration_v6_queues_existing_holders_on_idle() {
+	new_test_ext().execute_with(|| {
+		create_token(1, 500);
+		earn(1, 5, 100);
+		let _ = ExpiryQueue::<Test>::remove_all(None);
+		StorageVersion::new(5).put::<BrandToken>();
+
+		crate::migrations::v6::migrate::<Test>();
+		assert_eq!(ExpirySeedCursor::<Test>::get(), Some(Default::default()));
+		assert_eq!(BrandToken::on_chain_storage_version(), 6);
+
+		BrandToken::on_idle(1, u64::MAX);
+		assert_eq!(ExpiryQueue::<Test>::iter().count(), 1);
+		assert_eq!(ExpirySeedCursor::<Test>::get(), None);
+	});
+}
+
+#[test]
+fn expiry_seeding_resumes_from_the_cursor() {
+	new_test_ext().execute_with(|| {
+		BrandToken::integrity_test();
+		create_token(1, 500);
+		for who in 2..=6 {
+			earn(1, who, 10);
+		}
+		let _ = ExpiryQueue::<Test>::remove_all(None);
+		StorageVersion::new(5).put::<BrandToken>();
+		crate::migrations::v6::migrate::<Test>();
+
+		// `MaxExpiriesPerBlock` holders at a time
+		BrandToken::on_idle(1, u64::MAX);
+		assert_eq!(ExpiryQueue::<Test>::iter().count(), 4);
+		assert!(!ExpirySeedCursor::<Test>::get().unwrap().is_empty());
+
+		BrandToken::on_idle(2, u64::MAX);
+		assert_eq!(ExpiryQueue::<Test>::iter().count(), 5);
+		assert_eq!(ExpirySeedCursor::<Test>::get(), None);
+	});
+}
+
+#[test]
+fn migration_v7_indexes_exchange_rates_by_target() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(6).put::<BrandToken>();
+		let rate = ExchangeRate { rate: ratio(2), from_agreed: true, to_agreed: false };
+		ExchangeRates::<Test>::insert(1, 2, rate.clone());
+		ExchangeRates::<Test>::insert(3, 2, rate);
+
+		crate::migrations::v7::migrate::<Test>();
+
+		assert!(ExchangeRatesInto::<Test>::contains_key(2, 1));
+		assert!(ExchangeRatesInto::<Test>::contains_key(2, 3));
+		assert_eq!(BrandToken::on_chain_storage_version(), 7);
+	});
+}
+
+#[test]
+fn runtime_upgrade_runs_every_migration_in_turn() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<BrandToken>();
+		unhashed::put(
+			&BrandTokenById::<Test>::hashed_key_for(1),
+			&(b"PTS".to_vec(), 400u32, 500u32, 12u8),
+		);
+
+		BrandToken::on_runtime_upgrade();
+
+		assert_eq!(BrandTokenById::<Test>::get(1).unwrap().staked, 500);
+		assert_eq!(AssetIds::<Test>::get(1), Some(0));
+		assert_eq!(ExpirySeedCursor::<Test>::get(), Some(Default::default()));
+		assert_eq!(BrandToken::on_chain_storage_version(), 7);
+
+		// already up to date
+		BrandToken::on_runtime_upgrade();
+		assert_eq!(NextAssetId::<Test>::get(), 1);
+	});
+}
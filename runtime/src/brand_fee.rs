@@ -0,0 +1,187 @@
+//! Transaction fees payable in brand points or by a brand's sponsorship.
+
+use crate::{AccountId, Balance, BrandToken, Call, Runtime, TransactionPayment};
+use codec::{Decode, Encode};
+use pallet_brand_token::{AmountOf, CallIndex};
+use pallet_transaction_payment::ChargeTransactionPayment;
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, PostDispatchInfoOf, SaturatedConversion, SignedExtension},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	DispatchResult, RuntimeDebug,
+};
+use sp_std::vec::Vec;
+
+/// Who pays the fee of a transaction.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum FeePayer {
+	/// The signer, in the native currency.
+	Signer,
+	/// The signer, in points of the brand.
+	Points(AccountId),
+	/// The brand, out of its sponsorship budget.
+	Sponsor(AccountId),
+}
+
+/// Charges the transaction fee like `ChargeTransactionPayment`, or, as the signer picks, in a
+/// brand's points at the rate the brand published, or to a brand sponsoring the call. Either
+/// way the brand's reserve then pays the native fee.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ChargeBrandFee {
+	#[codec(compact)]
+	tip: Balance,
+	payer: FeePayer,
+}
+
+impl ChargeBrandFee {
+	/// Have `payer` pay the fee and `tip`.
+	pub fn new(tip: Balance, payer: FeePayer) -> Self {
+		Self { tip, payer }
+	}
+
+	/// Fee of the transaction and its price in points of `brand`.
+	fn fee_in_points(
+		&self,
+		brand: &AccountId,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Result<(Balance, Balance), TransactionValidityError> {
+		let fee = TransactionPayment::compute_fee(len as u32, info, self.tip);
+		let points = BrandToken::fee_in_points(brand, fee).ok_or(InvalidTransaction::Payment)?;
+		Ok((fee, points))
+	}
+}
+
+impl From<Balance> for ChargeBrandFee {
+	fn from(tip: Balance) -> Self {
+		Self::new(tip, FeePayer::Signer)
+	}
+}
+
+/// Pallet and call index of `call`, as sponsorships list them.
+fn call_index(call: &Call) -> CallIndex {
+	call.using_encoded(|encoded| [encoded[0], encoded.get(1).copied().unwrap_or_default()])
+}
+
+/// What `pre_dispatch` charged, for `post_dispatch` to settle.
+pub enum Pre {
+	Native(<ChargeTransactionPayment<Runtime> as SignedExtension>::Pre),
+	Points {
+		who: AccountId,
+		brand: AccountId,
+		tip: Balance,
+		fee: Balance,
+		grants: Vec<AmountOf<Runtime>>,
+	},
+	Sponsored { who: AccountId, brand: AccountId, tip: Balance, fee: Balance },
+}
+
+impl Default for Pre {
+	fn default() -> Self {
+		Pre::Native(Default::default())
+	}
+}
+
+impl SignedExtension for ChargeBrandFee {
+	const IDENTIFIER: &'static str = "ChargeBrandFee";
+	type AccountId = AccountId;
+	type Call = Call;
+	type AdditionalSigned = ();
+	type Pre = Pre;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &AccountId,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> TransactionValidity {
+		let can_pay = match &self.payer {
+			FeePayer::Signer =>
+				return ChargeTransactionPayment::<Runtime>::from(self.tip)
+					.validate(who, call, info, len),
+			FeePayer::Points(brand) => {
+				let (fee, points) = self.fee_in_points(brand, info, len)?;
+				BrandToken::can_pay_fee(brand, who, fee, points)
+			},
+			FeePayer::Sponsor(brand) => {
+				let fee = TransactionPayment::compute_fee(len as u32, info, self.tip);
+				BrandToken::can_sponsor(brand, who, call_index(call), fee)
+			},
+		};
+		if !can_pay {
+			return Err(InvalidTransaction::Payment.into())
+		}
+		Ok(ValidTransaction { priority: self.tip.saturated_into(), ..Default::default() })
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &AccountId,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Result<Pre, TransactionValidityError> {
+		match &self.payer {
+			FeePayer::Signer => ChargeTransactionPayment::<Runtime>::from(self.tip)
+				.pre_dispatch(who, call, info, len)
+				.map(Pre::Native),
+			FeePayer::Points(brand) => {
+				let (fee, points) = self.fee_in_points(brand, info, len)?;
+				let grants = BrandToken::withdraw_fee(brand, who, fee, points)
+					.map_err(|_| InvalidTransaction::Payment)?;
+				Ok(Pre::Points {
+					who: who.clone(),
+					brand: brand.clone(),
+					tip: self.tip,
+					fee,
+					grants,
+				})
+			},
+			FeePayer::Sponsor(brand) => {
+				let fee = TransactionPayment::compute_fee(len as u32, info, self.tip);
+				BrandToken::withdraw_sponsored_fee(brand, who, call_index(call), fee)
+					.map_err(|_| InvalidTransaction::Payment)?;
+				Ok(Pre::Sponsored { who: who.clone(), brand: brand.clone(), tip: self.tip, fee })
+			},
+		}
+	}
+
+	fn post_dispatch(
+		pre: Pre,
+		info: &DispatchInfoOf<Call>,
+		post_info: &PostDispatchInfoOf<Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		match pre {
+			Pre::Native(pre) =>
+				ChargeTransactionPayment::<Runtime>::post_dispatch(pre, info, post_info, len, result),
+			Pre::Points { who, brand, tip, fee, grants } => {
+				let actual_fee =
+					TransactionPayment::compute_actual_fee(len as u32, info, post_info, tip)
+						.min(fee);
+				let points =
+					grants.iter().fold(0, |sum: Balance, grant| sum.saturating_add(grant.amount));
+				let actual_points =
+					BrandToken::fee_in_points(&brand, actual_fee).unwrap_or(points).min(points);
+				// the call already ran, so settling must not fail
+				BrandToken::settle_fee(&brand, &who, actual_fee, grants, points - actual_points);
+				Ok(())
+			},
+			Pre::Sponsored { who, brand, tip, fee } => {
+				let actual_fee =
+					TransactionPayment::compute_actual_fee(len as u32, info, post_info, tip)
+						.min(fee);
+				BrandToken::settle_sponsored_fee(&brand, &who, fee, actual_fee);
+				Ok(())
+			},
+		}
+	}
+}
@@ -31,7 +31,7 @@ pub use frame_support::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
@@ -51,6 +51,9 @@ pub use pallet_brand_token;
 pub use pallet_brand_membership;
 pub use pallet_ocw;
 
+mod brand_fee;
+pub use brand_fee::{ChargeBrandFee, FeePayer};
+
 /// An index to a block.
 pub type BlockNumber = u32;
 
@@ -109,10 +112,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 113,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 3,
 	state_version: 1,
 };
 
@@ -298,16 +301,54 @@ impl pallet_template::Config for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	pub const BrandWindDownPeriod: BlockNumber = 30 * DAYS;
+	// a hundred times the existential deposit
+	pub const BrandDeposit: Balance = 50_000;
+	pub const CoalitionPalletId: PalletId = PalletId(*b"loy/coal");
+}
+
 impl pallet_brand_admin::Config for Runtime {
 	type Event = Event;
+	type DepositCurrency = Balances;
+	type ProfileDepositPerByte = DataDepositPerByte;
+	type MaxNameLen = ConstU32<64>;
+	type MaxProfileFieldLen = ConstU32<256>;
+	type BrandLifecycle = (BrandToken, BrandMembership);
+	type WindDownPeriod = BrandWindDownPeriod;
+	type VerifierOrigin = frame_system::EnsureRoot<AccountId>;
+	type BrandDeposit = BrandDeposit;
+	type Slashed = ();
+	type CoalitionPalletId = CoalitionPalletId;
+	type MaxCoalitionMembers = ConstU32<32>;
+}
+
+parameter_types! {
+	pub const PointHoldTimeout: BlockNumber = 2 * HOURS;
+	pub const PointRefundPeriod: BlockNumber = 30 * DAYS;
 }
 
 /// Configure the pallet-template in pallets/template.
 impl pallet_brand_token::Config for Runtime {
 	type Event = Event;
 	type Currency = Balances;
+	type PointBalance = Balance;
 	type Timestamp = Timestamp;
 	type Moment = u64;
+	type MaxSymbolLen = ConstU32<8>;
+	type MaxUtxoEntries = ConstU32<128>;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as Verify>::Signer;
+	type CollateralOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxExpiriesPerBlock = ConstU32<64>;
+	type MaxCampaigns = ConstU32<16>;
+	type MaxReferralDepth = ConstU32<32>;
+	type HoldTimeout = PointHoldTimeout;
+	type RefundPeriod = PointRefundPeriod;
+	type MaxHoldTimeoutsPerBlock = ConstU32<64>;
+	type ClawbackOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxReasonLen = ConstU32<128>;
+	type MaxSponsoredCalls = ConstU32<16>;
 }
 
 parameter_types! {
@@ -323,6 +364,7 @@ impl pallet_brand_membership::Config for Runtime {
 	type Timestamp = Timestamp;
 	type Moment = u64;
 	type BrandCurrency = BrandToken;
+	type MaxMediaLen = ConstU32<256>;
 }
 
 // for ocw
@@ -359,7 +401,7 @@ where
             frame_system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
             frame_system::CheckNonce::<Runtime>::from(index),
             frame_system::CheckWeight::<Runtime>::new(),
-            pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
+            ChargeBrandFee::from(tip),
         );
 
         #[cfg_attr(not(feature = "std"), allow(unused_variables))]
@@ -432,7 +474,7 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	ChargeBrandFee,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;